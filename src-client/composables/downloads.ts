@@ -2,6 +2,15 @@
 import { ref, computed } from 'vue';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from "@tauri-apps/api/core";
+import type {
+  DownloadPriority,
+  GameMeta,
+  QueuedDownload,
+  RateLimits,
+  TorrentFileEntry,
+  TorrentSettings,
+  VerifyReport,
+} from '~/interfaces/game';
 
 interface DownloadProgress {
   progress: number;
@@ -22,6 +31,7 @@ interface DownloadState {
 
 // Global state - shared across all components
 const downloads = ref<Map<number, DownloadState>>(new Map());
+const queue = ref<QueuedDownload[]>([]);
 
 // Track if event listeners have been initialized
 let listenersInitialized = false;
@@ -29,6 +39,16 @@ let progressUnlisten: UnlistenFn | null = null;
 let completeUnlisten: UnlistenFn | null = null;
 let errorUnlisten: UnlistenFn | null = null;
 let restoredUnlisten: UnlistenFn | null = null;
+let queuedUnlisten: UnlistenFn | null = null;
+let startedUnlisten: UnlistenFn | null = null;
+
+async function refreshQueue() {
+  try {
+    queue.value = await invoke<QueuedDownload[]>('get_download_queue');
+  } catch (error) {
+    console.error('[UI] Failed to load download queue:', error);
+  }
+}
 
 /**
  * Initialize global event listeners (only once)
@@ -86,6 +106,18 @@ async function initializeListeners() {
     }
   });
 
+  // Listen for downloads waiting for a free slot
+  queuedUnlisten = await listen<any>('download-queued', (event) => {
+    console.log(`[UI] Download for game ${event.payload.gameId} queued`);
+    refreshQueue();
+  });
+
+  // Listen for queued downloads that got a slot
+  startedUnlisten = await listen<any>('download-started', (event) => {
+    console.log(`[UI] Queued download for game ${event.payload.gameId} started`);
+    refreshQueue();
+  });
+
   // Listen for download errors
   errorUnlisten = await listen<any>('download-error', (event) => {
     const gameId = event.payload.gameId;
@@ -106,6 +138,8 @@ export function cleanupDownloadListeners() {
   if (completeUnlisten) completeUnlisten();
   if (errorUnlisten) errorUnlisten();
   if (restoredUnlisten) restoredUnlisten();
+  if (queuedUnlisten) queuedUnlisten();
+  if (startedUnlisten) startedUnlisten();
   listenersInitialized = false;
 }
 
@@ -227,6 +261,60 @@ export function useDownloads() {
     downloads.value.delete(gameId);
   };
 
+  /**
+   * Check if a game is waiting in the download queue
+   */
+  const isQueued = (gameId: number) => {
+    return queue.value.some((entry) => entry.gameId === gameId);
+  };
+
+  const setPriority = async (gameId: number, priority: DownloadPriority) => {
+    await invoke('set_download_priority', { gameId, priority });
+    await refreshQueue();
+  };
+
+  const reorderDownload = async (gameId: number, position: number) => {
+    await invoke('reorder_download', { gameId, position });
+    await refreshQueue();
+  };
+
+  const moveToTop = async (gameId: number) => {
+    await invoke('move_download_to_top', { gameId });
+    await refreshQueue();
+  };
+
+  /**
+   * Start the next queued download, ignoring the concurrency limit
+   */
+  const startNext = async () => {
+    const gameId = await invoke<number | null>('start_next_download');
+    await refreshQueue();
+    return gameId;
+  };
+
+  const getTorrentSettings = () => invoke<TorrentSettings>('get_torrent_settings');
+
+  const setTorrentSettings = (settings: TorrentSettings) =>
+    invoke('set_torrent_settings', { settings });
+
+  /**
+   * List the files in a game's torrent, to pick which ones to download
+   */
+  const inspectTorrent = (game: GameMeta) =>
+    invoke<TorrentFileEntry[]>('inspect_torrent', { invokeMessage: game });
+
+  /**
+   * Re-check a game's files against its torrent; bad pieces are downloaded again
+   */
+  const verifyDownload = (game: GameMeta) =>
+    invoke<VerifyReport>('verify_game_download', { invokeMessage: game });
+
+  const getGameRateLimits = (gameId: number) =>
+    invoke<RateLimits>('get_game_rate_limits', { gameId });
+
+  const setGameRateLimits = (gameId: number, rateLimits: RateLimits) =>
+    invoke('set_game_rate_limits', { gameId, rateLimits });
+
   /**
    * Get all active downloads
    */
@@ -248,6 +336,7 @@ export function useDownloads() {
     downloads,
     activeDownloads,
     activeDownloadCount,
+    queue,
 
     // Methods
     getDownloadState,
@@ -259,5 +348,17 @@ export function useDownloads() {
     setPaused,
     completeDownload,
     removeDownload,
+    isQueued,
+    refreshQueue,
+    setPriority,
+    reorderDownload,
+    moveToTop,
+    startNext,
+    getTorrentSettings,
+    setTorrentSettings,
+    getGameRateLimits,
+    setGameRateLimits,
+    inspectTorrent,
+    verifyDownload,
   };
 }
\ No newline at end of file
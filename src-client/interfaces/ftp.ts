@@ -6,11 +6,19 @@ export interface GameFile {
   filePath: string;
   fileName: string;
   fileSize: number;
+  installTarget?: InstallTarget | null;
+  unavailableReason?: string | null;
 }
 
+export type InstallTarget = "sd" | "nand";
+
 export type TransferStatus =
   | "queued"
+  | "waiting"
   | "transferring"
+  | "verifying"
+  | "retrying"
+  | "paused"
   | "completed"
   | "failed"
   | "cancelled";
@@ -24,6 +32,8 @@ export interface TransferProgress {
   transferSpeed: number; // bytes per second
   etaSeconds: number;
   status: TransferStatus;
+  attempt: number;
+  deviceId: string | null;
 }
 
 export interface QueueUpdatePayload {
@@ -33,10 +43,187 @@ export interface QueueUpdatePayload {
 export interface TransferCompletePayload {
   gameId: number;
   fileName: string;
+  deviceId: string | null;
 }
 
 export interface TransferErrorPayload {
   gameId: number;
   fileName: string;
+  deviceId: string | null;
+  error: string;
+  attempt: number;
+  retrying: boolean;
+}
+
+export interface FtpConnectionProfile {
+  host: string;
+  port: number;
+  username: string;
+  password?: string; // only sent, never returned; empty keeps the saved one
+  hasPassword: boolean;
+  useFtps: boolean;
+  acceptInvalidCerts: boolean;
+}
+
+export interface LocalInterface {
+  name: string;
+  ipAddress: string;
+  netmask: string;
+  prefixLen: number;
+  cidr: string;
+  isVirtual: boolean;
+}
+
+export interface DiscoverySettings {
+  interfaces: string[];
+  ranges: string[];
+  ports: number[];
+  probeFeatures: boolean;
+  scanVirtualInterfaces: boolean;
+  scanLargeSubnets: boolean;
+}
+
+export type FtpServerKind = "dbi" | "sys-ftpd" | "ftpd" | "unknown";
+
+export interface FoundService {
+  ipAddress: string;
+  port: number;
+  serverKind: FtpServerKind;
+  banner: string;
+  latencyMs: number;
+  macAddress: string | null;
+}
+
+export interface FTPStatusPayload {
+  isActive: boolean;
+  activeCount: number;
+  activeIps: string[];
+  services: FoundService[];
+}
+
+export interface RegisteredDevice {
+  id: string;
+  name: string;
+  lastIp: string;
+  port: number;
+  lastSeen: string | null;
+  serverKind: FtpServerKind;
+  preferred: boolean;
+  installTarget: InstallTarget | null;
+  macAddress: string | null;
+}
+
+export interface DeviceMerge {
+  from: string;
+  into: string;
+}
+
+export interface DeviceStatusPayload {
+  device: RegisteredDevice;
+  online: boolean;
+}
+
+export interface TransferTask {
+  gameFile: GameFile;
+  status: TransferStatus;
+  resumeOffset: number;
+  deviceId: string | null;
+  attempts: number;
+  failures: TransferFailure[];
+  bundle: BundleMembership | null;
+  uploadLimit: number | null; // bytes per second
+}
+
+export interface TransferFailure {
+  attempt: number;
   error: string;
+  transient: boolean;
+  failedAt: string;
+}
+
+export type RemoteEntryKind = "file" | "directory" | "symlink";
+
+export interface RemoteEntry {
+  name: string;
+  path: string;
+  size: number;
+  kind: RemoteEntryKind;
+  modified: string | null;
+}
+
+export interface TransferSettings {
+  verifyUploads: boolean;
+  retryFailedVerification: boolean;
+  maxAttempts: number;
+  retryDelaySecs: number;
+  maxRetryDelaySecs: number;
+  autoInstallDownloads: boolean;
+  uploadLimit: number | null; // bytes per second
+}
+
+export type ContentKind = "base" | "update" | "dlc";
+
+export interface BundleFile {
+  kind: ContentKind;
+  gameFile: GameFile;
+}
+
+export interface GameBundle {
+  gameId: number;
+  gameTitle: string;
+  totalSize: number;
+  files: BundleFile[];
+}
+
+export interface BundleMembership {
+  bundleId: string;
+  kind: ContentKind;
+  totalFiles: number;
+  totalBytes: number;
+}
+
+export interface BundleProgress {
+  bundleId: string;
+  deviceId: string | null;
+  gameId: number;
+  gameTitle: string;
+  currentFile: string;
+  currentKind: ContentKind;
+  filesCompleted: number;
+  filesFailed: number;
+  totalFiles: number;
+  bytesTransferred: number;
+  totalBytes: number;
+  progressPercent: number;
+  transferSpeed: number;
+  etaSeconds: number;
+  status: TransferStatus;
+}
+
+export interface AutoInstallPayload {
+  gameId: number;
+  deviceId: string | null;
+  deviceName: string | null;
+  bundle: GameBundle | null;
+  error: string | null;
+}
+
+export interface DeviceQueueResult {
+  deviceId: string;
+  queued: boolean;
+  error: string | null;
+}
+
+export interface ConnectionDiagnostics {
+  address: string;
+  useFtps: boolean;
+  tcpConnectMs: number | null;
+  loginMs: number | null;
+  uploadBytes: number;
+  uploadSecs: number | null;
+  uploadSpeed: number | null; // bytes per second
+  testFileRemoved: boolean;
+  transfersActive: boolean;
+  error: string | null;
+  testedAt: string;
 }
@@ -37,4 +37,43 @@ export interface TorrentStats {
   downloadSpeed: number;
   uploadSpeed: number;
   peers: number;
-}
\ No newline at end of file
+}
+export type DownloadPriority = 'high' | 'normal' | 'low';
+
+export interface QueuedDownload {
+  gameId: number;
+  position: number;
+  priority: DownloadPriority;
+}
+
+export interface RateLimits {
+  downloadLimit: number | null; // bytes per second
+  uploadLimit: number | null; // bytes per second
+}
+
+export interface TorrentSettings {
+  maxActiveDownloads: number;
+  rateLimits: RateLimits;
+}
+
+export interface TorrentFileEntry {
+  index: number;
+  path: string;
+  size: number;
+}
+
+export interface FileHealth {
+  index: number;
+  path: string;
+  size: number;
+  verifiedBytes: number;
+  healthy: boolean;
+}
+
+export interface VerifyReport {
+  gameId: number;
+  healthy: boolean;
+  corruptFiles: number;
+  repairing: boolean;
+  files: FileHealth[];
+}
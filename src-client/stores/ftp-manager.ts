@@ -8,6 +8,8 @@ import type {
   QueueUpdatePayload,
   TransferCompletePayload,
   TransferErrorPayload,
+  DeviceQueueResult,
+  ConnectionDiagnostics,
 } from "~/interfaces/ftp";
 
 interface FTPManagerState {
@@ -143,10 +145,10 @@ export const useFTPManagerStore = defineStore("ftpManager", {
     },
 
     // Queue a file for transfer
-    async queueFile(gameFile: GameFile) {
+    async queueFile(gameFile: GameFile, deviceId?: string) {
       try {
         this.error = null;
-        const result = await invoke<string>("queue_file", { gameFile });
+        const result = await invoke<string>("queue_file", { gameFile, deviceId });
         console.log(result);
         await this.refreshQueue();
         return result;
@@ -156,6 +158,22 @@ export const useFTPManagerStore = defineStore("ftpManager", {
       }
     },
 
+    // Queue the same file to several consoles at once
+    async queueFileToDevices(gameFile: GameFile, deviceIds: string[]) {
+      try {
+        this.error = null;
+        const results = await invoke<DeviceQueueResult[]>(
+          "queue_file_to_devices",
+          { gameFile, deviceIds }
+        );
+        await this.refreshQueue();
+        return results;
+      } catch (error) {
+        this.error = error as string;
+        throw error;
+      }
+    },
+
     // Get current transfer queue
     async refreshQueue() {
       try {
@@ -183,11 +201,12 @@ export const useFTPManagerStore = defineStore("ftpManager", {
     },
 
     // Remove specific file from queue
-    async removeFromQueue(filePath: string) {
+    async removeFromQueue(filePath: string, deviceId?: string) {
       try {
         this.error = null;
         const result = await invoke<string>("remove_from_transfer_queue", {
           filePath,
+          deviceId,
         });
         await this.refreshQueue();
         console.log(result);
@@ -198,6 +217,37 @@ export const useFTPManagerStore = defineStore("ftpManager", {
       }
     },
 
+    // Queue failed files again, all of them without a file path
+    async retryFailedTransfers(filePath?: string, deviceId?: string) {
+      try {
+        this.error = null;
+        const result = await invoke<string>("retry_failed_transfers", {
+          filePath,
+          deviceId,
+        });
+        await this.refreshQueue();
+        console.log(result);
+        return result;
+      } catch (error) {
+        this.error = error as string;
+        throw error;
+      }
+    },
+
+    // Drop every failed file from the queue
+    async clearFailedTransfers() {
+      try {
+        this.error = null;
+        const result = await invoke<string>("clear_failed_transfers");
+        await this.refreshQueue();
+        console.log(result);
+        return result;
+      } catch (error) {
+        this.error = error as string;
+        throw error;
+      }
+    },
+
     // Get current transfer status
     async getCurrentTransfer() {
       try {
@@ -212,6 +262,20 @@ export const useFTPManagerStore = defineStore("ftpManager", {
       }
     },
 
+    // Measure latency, login time and upload speed to a console
+    async diagnoseConnection(deviceId?: string, testSizeMb?: number) {
+      try {
+        this.error = null;
+        return await invoke<ConnectionDiagnostics>("diagnose_ftp_connection", {
+          deviceId,
+          testSizeMb,
+        });
+      } catch (error) {
+        this.error = error as string;
+        throw error;
+      }
+    },
+
     // Check if currently transferring
     async checkIsTransferring() {
       try {
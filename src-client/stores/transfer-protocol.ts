@@ -43,27 +43,14 @@ export const useTransferProtocolStore = defineStore('transferProtocol', {
           this.isFTPActive = event.payload.isActive;
           this.ftpIps = event.payload.activeIps;
 
-          // Automatically set FTP IP in the manager when detected
+          // The backend points the manager at the preferred registered console,
+          // so only mirror whichever address it picked
           if (event.payload.isActive && event.payload.activeIps.length > 0) {
             const ftpManagerStore = useFTPManagerStore();
-            const primaryIp = event.payload.activeIps[0];
-            
-            // Only set if not already set or if IP changed
             const currentIp = await ftpManagerStore.getFtpIp();
-            if (primaryIp && currentIp !== primaryIp) {
-              try {
-                await ftpManagerStore.setFtpIp(primaryIp);
-                this.selectedFtpIp = primaryIp;
-                console.log(`✓ FTP IP automatically set to: ${primaryIp}`);
-              } catch (error) {
-                console.error('Failed to set FTP IP:', error);
-                // Even if setting fails, mark as active since we detected it
-                this.isFTPActive = true;
-              }
-            } else if (currentIp) {
-              // IP already set and matches, just update selectedFtpIp
-              this.selectedFtpIp = currentIp;
-              console.log(`✓ FTP connection maintained: ${currentIp}`);
+            this.selectedFtpIp = currentIp;
+            if (currentIp) {
+              console.log(`✓ FTP target: ${currentIp}`);
             }
           } else {
             // No active FTP connections
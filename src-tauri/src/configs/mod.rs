@@ -1,2 +1,3 @@
 pub mod constants;
 pub mod defaults;
+pub mod storage;
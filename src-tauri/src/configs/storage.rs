@@ -0,0 +1,48 @@
+use log::{error, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+
+use crate::configs::defaults::get_config_path;
+
+/// Reads a JSON file from the config directory, returning `None` if it is missing or invalid.
+pub fn load_config_file<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = get_config_path().join(file_name);
+
+    if !path.exists() {
+        return None;
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(data) => match serde_json::from_str::<T>(&data) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Failed to parse config file {:?}: {}", path, e);
+                None
+            }
+        },
+        Err(e) => {
+            warn!("Failed to read config file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Writes a value as JSON into the config directory, replacing the file atomically.
+pub fn save_config_file<T: Serialize>(file_name: &str, value: &T) -> Result<(), String> {
+    let path = get_config_path().join(file_name);
+    let tmp_path = path.with_extension("tmp");
+
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file_name, e))?;
+
+    fs::write(&tmp_path, json).map_err(|e| {
+        error!("Failed to write config file {:?}: {}", tmp_path, e);
+        format!("Failed to write {}: {}", file_name, e)
+    })?;
+
+    fs::rename(&tmp_path, &path).map_err(|e| {
+        error!("Failed to replace config file {:?}: {}", path, e);
+        format!("Failed to save {}: {}", file_name, e)
+    })
+}
@@ -0,0 +1,435 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use super::ftp_discovery::{FoundService, FtpServerKind};
+use super::ftp_manager::{FTPManager, InstallTarget};
+use crate::configs::storage::{load_config_file, save_config_file};
+
+const DEVICE_REGISTRY_FILE: &str = ".ftp_devices.json";
+
+/// A console that discovery has seen at least once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredDevice {
+    pub id: String,
+    pub name: String,
+    pub last_ip: String,
+    pub port: u16,
+    /// RFC 3339 timestamp of the last scan that found this console.
+    pub last_seen: Option<String>,
+    pub server_kind: FtpServerKind,
+    #[serde(default)]
+    pub preferred: bool,
+    /// Install destination used for files queued to this console without one.
+    #[serde(default)]
+    pub install_target: Option<InstallTarget>,
+    /// Hardware address from the neighbour table, which survives DHCP lease changes.
+    #[serde(default)]
+    pub mac_address: Option<String>,
+}
+
+impl RegisteredDevice {
+    /// Whether a scan result is this console, going by MAC address when both sides have
+    /// one and by IP address otherwise.
+    fn matches(&self, service: &FoundService) -> bool {
+        if self.port != service.port {
+            return false;
+        }
+        match (&self.mac_address, &service.mac_address) {
+            (Some(known), Some(seen)) => known == seen,
+            _ => self.last_ip == service.ip_address,
+        }
+    }
+}
+
+/// A duplicate entry folded into another one; tasks bound to `from` now belong to `into`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceMerge {
+    pub from: String,
+    pub into: String,
+}
+
+/// What a scan changed in the registry.
+#[derive(Debug, Default)]
+pub struct ScanChanges {
+    pub came_online: Vec<RegisteredDevice>,
+    pub went_offline: Vec<RegisteredDevice>,
+    pub merged: Vec<DeviceMerge>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceStatusPayload {
+    pub device: RegisteredDevice,
+    pub online: bool,
+}
+
+/// Remembered consoles, saved to `.ftp_devices.json`, plus which of them answered the last scan.
+pub struct DeviceRegistry {
+    devices: Mutex<Vec<RegisteredDevice>>,
+    online: Mutex<HashSet<String>>,
+}
+
+impl DeviceRegistry {
+    pub fn load() -> Self {
+        let devices: Vec<RegisteredDevice> =
+            load_config_file(DEVICE_REGISTRY_FILE).unwrap_or_default();
+        info!("Loaded {} registered console(s)", devices.len());
+
+        Self {
+            devices: Mutex::new(devices),
+            online: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn save(devices: &[RegisteredDevice]) {
+        if let Err(e) = save_config_file(DEVICE_REGISTRY_FILE, &devices) {
+            warn!("Failed to save device registry: {}", e);
+        }
+    }
+
+    pub fn devices(&self) -> Vec<RegisteredDevice> {
+        self.devices.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, device_id: &str) -> Option<RegisteredDevice> {
+        self.devices
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|device| device.id == device_id)
+            .cloned()
+    }
+
+    pub fn is_online(&self, device_id: &str) -> bool {
+        self.online.lock().unwrap().contains(device_id)
+    }
+
+    pub fn online_devices(&self) -> Vec<RegisteredDevice> {
+        // Always take `devices` before `online` to match update_from_scan
+        let devices = self.devices.lock().unwrap();
+        let online = self.online.lock().unwrap();
+        devices
+            .iter()
+            .filter(|device| online.contains(&device.id))
+            .cloned()
+            .collect()
+    }
+
+    /// The console transfers should go to: the preferred one if it is online, otherwise
+    /// whichever online console was registered first.
+    pub fn preferred_target(&self) -> Option<RegisteredDevice> {
+        let online_devices = self.online_devices();
+        online_devices
+            .iter()
+            .find(|device| device.preferred)
+            .or_else(|| online_devices.first())
+            .cloned()
+    }
+
+    /// Records the results of a completed scan, registering consoles seen for the first
+    /// time and following known ones to new IP addresses. Entries that turn out to be the
+    /// same console are merged into the oldest one.
+    pub fn update_from_scan(&self, services: &[FoundService]) -> ScanChanges {
+        let now = chrono::Local::now().to_rfc3339();
+        let mut devices = self.devices.lock().unwrap();
+        let mut online = self.online.lock().unwrap();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut merged = Vec::new();
+
+        for service in services {
+            if let Some(index) = devices.iter().position(|device| device.matches(service)) {
+                let device = &mut devices[index];
+                if device.last_ip != service.ip_address {
+                    info!(
+                        "Console {} moved from {} to {}",
+                        device.id, device.last_ip, service.ip_address
+                    );
+                    device.last_ip = service.ip_address.clone();
+                }
+                if device.mac_address.is_none() {
+                    device.mac_address = service.mac_address.clone();
+                }
+                device.last_seen = Some(now.clone());
+                device.server_kind = service.server_kind;
+                let into = device.id.clone();
+                seen.insert(into.clone());
+
+                // Entries registered for the same hardware under an older address
+                let duplicates: Vec<String> = devices
+                    .iter()
+                    .filter(|other| {
+                        other.id != into
+                            && other.port == service.port
+                            && match (&other.mac_address, &service.mac_address) {
+                                (Some(known), Some(seen)) => known == seen,
+                                (None, Some(_)) => other.last_ip == service.ip_address,
+                                _ => false,
+                            }
+                    })
+                    .map(|other| other.id.clone())
+                    .collect();
+                for from in duplicates {
+                    Self::absorb(&mut devices, &mut online, &from, &into);
+                    merged.push(DeviceMerge {
+                        from,
+                        into: into.clone(),
+                    });
+                }
+                continue;
+            }
+
+            let device = RegisteredDevice {
+                id: Self::new_device_id(&devices),
+                name: format!("Console at {}", service.ip_address),
+                last_ip: service.ip_address.clone(),
+                port: service.port,
+                last_seen: Some(now.clone()),
+                server_kind: service.server_kind,
+                // The very first console becomes the default target
+                preferred: !devices.iter().any(|device| device.preferred),
+                // Only DBI has install folders, other servers take files as-is
+                install_target: (service.server_kind == FtpServerKind::Dbi)
+                    .then_some(InstallTarget::Sd),
+                mac_address: service.mac_address.clone(),
+            };
+            info!(
+                "Registered new console {} at {}:{}",
+                device.id, device.last_ip, device.port
+            );
+            seen.insert(device.id.clone());
+            devices.push(device);
+        }
+
+        let came_online: Vec<RegisteredDevice> = devices
+            .iter()
+            .filter(|device| seen.contains(&device.id) && !online.contains(&device.id))
+            .cloned()
+            .collect();
+        let went_offline: Vec<RegisteredDevice> = devices
+            .iter()
+            .filter(|device| online.contains(&device.id) && !seen.contains(&device.id))
+            .cloned()
+            .collect();
+
+        *online = seen;
+        Self::save(&devices);
+
+        ScanChanges {
+            came_online,
+            went_offline,
+            merged,
+        }
+    }
+
+    /// Removes `from`, handing its preferred flag, and its name if `into` still has the
+    /// generated one, over to `into`.
+    fn absorb(
+        devices: &mut Vec<RegisteredDevice>,
+        online: &mut HashSet<String>,
+        from: &str,
+        into: &str,
+    ) {
+        let Some(index) = devices.iter().position(|device| device.id == from) else {
+            return;
+        };
+        let removed = devices.remove(index);
+        online.remove(from);
+
+        if let Some(survivor) = devices.iter_mut().find(|device| device.id == into) {
+            survivor.preferred |= removed.preferred;
+            if survivor.name.starts_with("Console at ") && !removed.name.starts_with("Console at ")
+            {
+                survivor.name = removed.name;
+            }
+            if survivor.mac_address.is_none() {
+                survivor.mac_address = removed.mac_address;
+            }
+        }
+        info!("Merged console {} into {}", from, into);
+    }
+
+    /// Folds one entry into another when the user confirms they are the same console,
+    /// e.g. one whose MAC address could not be read.
+    pub fn merge(&self, from: &str, into: &str) -> Result<RegisteredDevice, String> {
+        if from == into {
+            return Err("Cannot merge a device into itself".to_string());
+        }
+
+        let mut devices = self.devices.lock().unwrap();
+        let mut online = self.online.lock().unwrap();
+        for device_id in [from, into] {
+            if !devices.iter().any(|device| device.id == device_id) {
+                return Err(format!("Unknown device: {}", device_id));
+            }
+        }
+
+        Self::absorb(&mut devices, &mut online, from, into);
+        Self::save(&devices);
+        Ok(devices
+            .iter()
+            .find(|device| device.id == into)
+            .cloned()
+            .expect("merge target checked above"))
+    }
+
+    fn new_device_id(devices: &[RegisteredDevice]) -> String {
+        let base = format!("console-{}", chrono::Local::now().format("%Y%m%d%H%M%S%3f"));
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while devices.iter().any(|device| device.id == id) {
+            suffix += 1;
+            id = format!("{}-{}", base, suffix);
+        }
+        id
+    }
+
+    pub fn rename(&self, device_id: &str, name: String) -> Result<RegisteredDevice, String> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices
+            .iter_mut()
+            .find(|device| device.id == device_id)
+            .ok_or_else(|| format!("Unknown device: {}", device_id))?;
+
+        device.name = name;
+        let renamed = device.clone();
+        Self::save(&devices);
+        Ok(renamed)
+    }
+
+    pub fn set_preferred(&self, device_id: &str) -> Result<(), String> {
+        let mut devices = self.devices.lock().unwrap();
+        if !devices.iter().any(|device| device.id == device_id) {
+            return Err(format!("Unknown device: {}", device_id));
+        }
+
+        for device in devices.iter_mut() {
+            device.preferred = device.id == device_id;
+        }
+        Self::save(&devices);
+        Ok(())
+    }
+
+    pub fn set_install_target(
+        &self,
+        device_id: &str,
+        install_target: Option<InstallTarget>,
+    ) -> Result<RegisteredDevice, String> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices
+            .iter_mut()
+            .find(|device| device.id == device_id)
+            .ok_or_else(|| format!("Unknown device: {}", device_id))?;
+
+        device.install_target = install_target;
+        let updated = device.clone();
+        Self::save(&devices);
+        Ok(updated)
+    }
+
+    pub fn forget(&self, device_id: &str) -> Result<(), String> {
+        let mut devices = self.devices.lock().unwrap();
+        let original_len = devices.len();
+        devices.retain(|device| device.id != device_id);
+
+        if devices.len() == original_len {
+            return Err(format!("Unknown device: {}", device_id));
+        }
+
+        self.online.lock().unwrap().remove(device_id);
+        Self::save(&devices);
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_registered_devices(
+    state: tauri::State<Arc<DeviceRegistry>>,
+) -> Vec<DeviceStatusPayload> {
+    state
+        .devices()
+        .into_iter()
+        .map(|device| DeviceStatusPayload {
+            online: state.is_online(&device.id),
+            device,
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn rename_device(
+    device_id: String,
+    name: String,
+    state: tauri::State<Arc<DeviceRegistry>>,
+) -> Result<RegisteredDevice, String> {
+    state.rename(&device_id, name)
+}
+
+#[tauri::command]
+pub fn set_preferred_device(
+    device_id: String,
+    state: tauri::State<Arc<DeviceRegistry>>,
+    manager_state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    state.set_preferred(&device_id)?;
+
+    // Point the transfer manager at the new preferred console straight away
+    if let Some(manager) = manager_state.lock().as_ref() {
+        manager.handle_devices_changed(&state);
+    }
+
+    Ok(format!("Preferred device set to {}", device_id))
+}
+
+#[tauri::command]
+pub fn set_device_install_target(
+    device_id: String,
+    install_target: Option<InstallTarget>,
+    state: tauri::State<Arc<DeviceRegistry>>,
+) -> Result<RegisteredDevice, String> {
+    state.set_install_target(&device_id, install_target)
+}
+
+/// Merges `device_id` into `into_device_id`, moving its queued transfers along.
+#[tauri::command]
+pub fn merge_devices(
+    device_id: String,
+    into_device_id: String,
+    state: tauri::State<Arc<DeviceRegistry>>,
+    manager_state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<RegisteredDevice, String> {
+    let merged = state.merge(&device_id, &into_device_id)?;
+
+    if let Some(manager) = manager_state.lock().as_ref() {
+        manager.reassign_device(&device_id, Some(&into_device_id));
+        manager.handle_devices_changed(&state);
+    }
+
+    Ok(merged)
+}
+
+#[tauri::command]
+pub fn forget_device(
+    device_id: String,
+    state: tauri::State<Arc<DeviceRegistry>>,
+    manager_state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    state.forget(&device_id)?;
+
+    // Transfers bound to the console would wait for it forever, send them to the
+    // preferred console instead
+    if let Some(manager) = manager_state.lock().as_ref() {
+        let preferred = state
+            .devices()
+            .into_iter()
+            .find(|device| device.preferred)
+            .map(|device| device.id);
+        manager.reassign_device(&device_id, preferred.as_deref());
+        manager.handle_devices_changed(&state);
+    }
+
+    Ok(format!("Device {} forgotten", device_id))
+}
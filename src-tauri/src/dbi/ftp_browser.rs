@@ -0,0 +1,322 @@
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use suppaftp::list;
+
+use super::device_registry::DeviceRegistry;
+use super::ftp_connection::{self, FtpConnection, FtpConnectionProfile};
+use super::ftp_manager::FTPManager;
+use super::local_lock::LocalFileLock;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteEntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// One entry of a directory listing on the console.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteEntry {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub kind: RemoteEntryKind,
+    /// RFC 3339 timestamp, when the server reports one.
+    pub modified: Option<String>,
+}
+
+/// Joins a remote directory and a name without doubling up slashes.
+pub fn join_remote_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() || dir == "/" {
+        format!("/{}", name.trim_start_matches('/'))
+    } else {
+        format!(
+            "{}/{}",
+            dir.trim_end_matches('/'),
+            name.trim_start_matches('/')
+        )
+    }
+}
+
+/// Turns raw LIST output into typed entries. Lines the parser does not understand
+/// (totals, blank lines, odd server formats) are skipped.
+pub fn parse_listing(dir: &str, lines: &[String]) -> Vec<RemoteEntry> {
+    let mut entries: Vec<RemoteEntry> = lines
+        .iter()
+        .filter_map(|line| match list::File::from_str(line) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                debug!("Skipping unparsable LIST line {:?}: {:?}", line, e);
+                None
+            }
+        })
+        .filter(|file| file.name() != "." && file.name() != "..")
+        .map(|file| {
+            let kind = if file.is_directory() {
+                RemoteEntryKind::Directory
+            } else if file.is_symlink() {
+                RemoteEntryKind::Symlink
+            } else {
+                RemoteEntryKind::File
+            };
+
+            RemoteEntry {
+                name: file.name().to_string(),
+                path: join_remote_path(dir, file.name()),
+                size: file.size() as u64,
+                kind,
+                modified: Some(
+                    chrono::DateTime::<chrono::Local>::from(file.modified()).to_rfc3339(),
+                ),
+            }
+        })
+        .collect();
+
+    // Folders first, then by name, like most file managers
+    entries.sort_by(|a, b| {
+        (b.kind == RemoteEntryKind::Directory)
+            .cmp(&(a.kind == RemoteEntryKind::Directory))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    entries
+}
+
+pub fn list_dir(ftp: &mut FtpConnection, path: &str) -> Result<Vec<RemoteEntry>, String> {
+    let lines = ftp
+        .list(Some(path))
+        .map_err(|e| format!("Failed to list {}: {}", path, e))?;
+    Ok(parse_listing(path, &lines))
+}
+
+/// Resolves which console a browser command talks to: the given registered device, or
+/// whichever console the transfer manager currently targets.
+pub fn resolve_profile(
+    device_id: Option<String>,
+    registry: &DeviceRegistry,
+    manager_state: &parking_lot::Mutex<Option<FTPManager>>,
+) -> Result<FtpConnectionProfile, String> {
+    if let Some(device_id) = device_id {
+        let device = registry
+            .get(&device_id)
+            .ok_or_else(|| format!("Unknown device: {}", device_id))?;
+        if !registry.is_online(&device_id) {
+            return Err(format!("Console {} is offline", device.name));
+        }
+
+        let mut profile = ftp_connection::profile_for_host(&device.last_ip);
+        profile.port = device.port;
+        return Ok(profile);
+    }
+
+    let manager_guard = manager_state.lock();
+    let manager = manager_guard
+        .as_ref()
+        .ok_or_else(|| "FTP Manager not initialized".to_string())?;
+    manager
+        .get_ftp_profile()
+        .ok_or_else(|| "No console connected".to_string())
+}
+
+/// Connects with the profile and runs `op` on a blocking thread, closing the session afterwards.
+async fn with_connection<T, F>(profile: FtpConnectionProfile, op: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut FtpConnection) -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut ftp = profile.connect()?;
+        let result = op(&mut ftp);
+        ftp.quit().ok();
+        result
+    })
+    .await
+    .map_err(|e| format!("FTP task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn list_remote_dir(
+    path: String,
+    device_id: Option<String>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    manager_state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<Vec<RemoteEntry>, String> {
+    let profile = resolve_profile(device_id, &registry, &manager_state)?;
+    with_connection(profile, move |ftp| list_dir(ftp, &path)).await
+}
+
+#[tauri::command]
+pub async fn remote_mkdir(
+    path: String,
+    device_id: Option<String>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    manager_state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let profile = resolve_profile(device_id, &registry, &manager_state)?;
+    with_connection(profile, move |ftp| {
+        ftp.mkdir(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        info!("Created remote directory {}", path);
+        Ok(format!("Created {}", path))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remote_delete(
+    path: String,
+    device_id: Option<String>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    manager_state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let profile = resolve_profile(device_id, &registry, &manager_state)?;
+    with_connection(profile, move |ftp| {
+        // DELE only works on files, so fall back to RMD for (empty) directories
+        if let Err(file_err) = ftp.rm(&path) {
+            ftp.rmdir(&path).map_err(|dir_err| {
+                format!("Failed to delete {}: {} / {}", path, file_err, dir_err)
+            })?;
+        }
+        info!("Deleted remote path {}", path);
+        Ok(format!("Deleted {}", path))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remote_rename(
+    from: String,
+    to: String,
+    device_id: Option<String>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    manager_state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let profile = resolve_profile(device_id, &registry, &manager_state)?;
+    with_connection(profile, move |ftp| {
+        ftp.rename(&from, &to)
+            .map_err(|e| format!("Failed to rename {} to {}: {}", from, to, e))?;
+        info!("Renamed remote path {} -> {}", from, to);
+        Ok(format!("Renamed {} to {}", from, to))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn download_remote_file(
+    remote_path: String,
+    local_path: String,
+    device_id: Option<String>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    manager_state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<u64, String> {
+    let profile = resolve_profile(device_id, &registry, &manager_state)?;
+    with_connection(profile, move |ftp| {
+        let _lock = LocalFileLock::write(Path::new(&local_path))?;
+
+        // Fail on a bad remote path before touching the local file
+        let mut stream = ftp
+            .retr_as_stream(&remote_path)
+            .map_err(|e| format!("Failed to download {}: {}", remote_path, e))?;
+
+        // Download next to the target and only replace it once the whole file arrived
+        let tmp_path = format!("{}.part", local_path);
+        let copied = File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create {}: {}", tmp_path, e))
+            .and_then(|mut local| {
+                io::copy(&mut stream, &mut local)
+                    .map_err(|e| format!("Failed to download {}: {}", remote_path, e))
+            });
+
+        // Always close the data channel, even if the copy failed half-way
+        let finalized = ftp
+            .finalize_retr_stream(stream)
+            .map_err(|e| format!("Failed to finish download of {}: {}", remote_path, e));
+
+        let bytes = match copied.and_then(|bytes| finalized.map(|_| bytes)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if let Err(remove_err) = std::fs::remove_file(&tmp_path) {
+                    warn!(
+                        "Failed to remove partial download {}: {}",
+                        tmp_path, remove_err
+                    );
+                }
+                return Err(e);
+            }
+        };
+        std::fs::rename(&tmp_path, &local_path)
+            .map_err(|e| format!("Failed to move download to {}: {}", local_path, e))?;
+
+        info!(
+            "Downloaded {} -> {} ({} bytes)",
+            remote_path, local_path, bytes
+        );
+        Ok(bytes)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_remote_path_handles_the_root() {
+        assert_eq!(join_remote_path("/", "Games"), "/Games");
+        assert_eq!(join_remote_path("", "Games"), "/Games");
+        assert_eq!(join_remote_path("/", "/Games"), "/Games");
+    }
+
+    #[test]
+    fn join_remote_path_does_not_double_slashes() {
+        assert_eq!(join_remote_path("/Games", "game.nsp"), "/Games/game.nsp");
+        assert_eq!(join_remote_path("/Games/", "game.nsp"), "/Games/game.nsp");
+        assert_eq!(join_remote_path("/Games//", "/game.nsp"), "/Games/game.nsp");
+    }
+
+    #[test]
+    fn parse_listing_types_and_sorts_entries() {
+        let lines: Vec<String> = [
+            "total 3",
+            "",
+            "drwxr-xr-x 1 root root 0 Jan 01 00:00 .",
+            "drwxr-xr-x 1 root root 0 Jan 01 00:00 ..",
+            "-rw-r--r-- 1 root root 1048576 Mar 14 2024 game.nsp",
+            "drwxr-xr-x 1 root root 0 Jan 01 00:00 saves",
+            "lrwxrwxrwx 1 root root 7 Jan 01 00:00 latest -> game.nsp",
+            "drwxr-xr-x 1 root root 0 Jan 01 00:00 Album",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let entries = parse_listing("/switch/", &lines);
+        let summary: Vec<(&str, &str, RemoteEntryKind)> = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.path.as_str(), entry.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Album", "/switch/Album", RemoteEntryKind::Directory),
+                ("saves", "/switch/saves", RemoteEntryKind::Directory),
+                ("game.nsp", "/switch/game.nsp", RemoteEntryKind::File),
+                ("latest", "/switch/latest", RemoteEntryKind::Symlink),
+            ]
+        );
+        assert_eq!(entries[2].size, 1048576);
+        assert!(entries.iter().all(|entry| entry.modified.is_some()));
+    }
+
+    #[test]
+    fn parse_listing_skips_unparsable_lines() {
+        let lines = vec!["not a listing".to_string(), "226 Done".to_string()];
+        assert!(parse_listing("/", &lines).is_empty());
+    }
+}
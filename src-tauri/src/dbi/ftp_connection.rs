@@ -0,0 +1,324 @@
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+use suppaftp::native_tls::TlsConnector;
+use suppaftp::types::FileType;
+use suppaftp::{NativeTlsConnector, NativeTlsFtpStream};
+
+use crate::configs::storage::{load_config_file, save_config_file};
+
+pub const DBI_FTP_PORT: u16 = 5000;
+pub const FTP_TIMEOUT_MS: u64 = 5000;
+const FTP_PROFILES_FILE: &str = ".ftp_profiles.json";
+/// Service name FTP passwords are stored under in the OS keyring, one entry per host.
+const KEYRING_SERVICE: &str = "nx-shard-ftp";
+
+/// Saved profiles, read once from disk and the keyring and kept up to date on save and
+/// remove. Queue code looks profiles up while holding its locks, so this must not block.
+static PROFILES: Lazy<Mutex<Option<HashMap<String, FtpConnectionProfile>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Control connection to a console. Plain FTP sessions use the same type and are
+/// only upgraded to TLS when the profile asks for FTPS.
+pub type FtpConnection = NativeTlsFtpStream;
+
+/// Why talking to a console failed, split by whether trying again can help.
+#[derive(Debug, Clone)]
+pub enum TransferError {
+    /// The console dropped out, timed out or refused for now. Worth retrying.
+    Transient(String),
+    /// Bad address, credentials, local file or console setup. Retrying will not help.
+    Fatal(String),
+}
+
+impl TransferError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, TransferError::Transient(_))
+    }
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::Transient(message) | TransferError::Fatal(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl From<TransferError> for String {
+    fn from(error: TransferError) -> Self {
+        error.to_string()
+    }
+}
+
+/// How to reach and log in to the FTP server running on a console.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FtpConnectionProfile {
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_username")]
+    pub username: String,
+    /// Only ever received from the frontend. Saved passwords live in the OS keyring and
+    /// are neither written to `.ftp_profiles.json` nor sent back; an empty password
+    /// keeps the one already saved for the host.
+    #[serde(default, skip_serializing)]
+    pub password: String,
+    /// Whether a password other than the anonymous default is saved for the host.
+    #[serde(default)]
+    pub has_password: bool,
+    /// Upgrade the control and data channels with explicit FTPS (AUTH TLS).
+    #[serde(default)]
+    pub use_ftps: bool,
+    /// Skip certificate checks for FTPS. Homebrew servers usually ship self-signed
+    /// certificates, but trusting them has to be switched on per profile.
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+}
+
+fn default_port() -> u16 {
+    DBI_FTP_PORT
+}
+
+fn default_username() -> String {
+    "anonymous".to_string()
+}
+
+fn default_password() -> String {
+    "anonymous".to_string()
+}
+
+impl FtpConnectionProfile {
+    /// Default DBI profile for a host: port 5000, anonymous login, no TLS.
+    pub fn anonymous(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            port: default_port(),
+            username: default_username(),
+            password: default_password(),
+            has_password: false,
+            use_ftps: false,
+            accept_invalid_certs: false,
+        }
+    }
+
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub fn socket_addr(&self) -> Result<SocketAddr, TransferError> {
+        (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| {
+                TransferError::Fatal(format!("Invalid FTP address {}: {}", self.address(), e))
+            })?
+            .next()
+            .ok_or_else(|| {
+                TransferError::Fatal(format!("Could not resolve FTP address {}", self.address()))
+            })
+    }
+
+    /// Checks that something is listening on the profile's port without logging in.
+    pub fn test_tcp(&self) -> Result<(), TransferError> {
+        let addr = self.socket_addr()?;
+        TcpStream::connect_timeout(&addr, Duration::from_millis(FTP_TIMEOUT_MS))
+            .map(|_| ())
+            .map_err(|e| {
+                TransferError::Transient(format!(
+                    "Cannot connect to FTP server at {}: {}",
+                    self.address(),
+                    e
+                ))
+            })
+    }
+
+    /// Opens a logged-in, binary-mode session using this profile.
+    pub fn connect(&self) -> Result<FtpConnection, TransferError> {
+        let addr = self.socket_addr()?;
+        debug!("Connecting to FTP server at {}", self.address());
+
+        let mut ftp = FtpConnection::connect_timeout(addr, Duration::from_millis(FTP_TIMEOUT_MS))
+            .map_err(|e| {
+            TransferError::Transient(format!("Failed to connect to FTP server: {}", e))
+        })?;
+
+        if self.use_ftps {
+            let connector = TlsConnector::builder()
+                .danger_accept_invalid_certs(self.accept_invalid_certs)
+                .danger_accept_invalid_hostnames(self.accept_invalid_certs)
+                .build()
+                .map_err(|e| TransferError::Fatal(format!("Failed to set up TLS: {}", e)))?;
+
+            ftp = ftp
+                .into_secure(NativeTlsConnector::from(connector), &self.host)
+                .map_err(|e| {
+                    TransferError::Transient(if self.accept_invalid_certs {
+                        format!("FTPS negotiation failed: {}", e)
+                    } else {
+                        format!(
+                            "FTPS negotiation failed: {} (allow untrusted certificates in the profile if the server uses a self-signed one)",
+                            e
+                        )
+                    })
+                })?;
+        }
+
+        ftp.login(&self.username, &self.password)
+            .map_err(|e| TransferError::Fatal(format!("FTP login failed: {}", e)))?;
+
+        ftp.transfer_type(FileType::Binary)
+            .map_err(|e| TransferError::Transient(format!("Failed to set binary mode: {}", e)))?;
+
+        Ok(ftp)
+    }
+}
+
+fn keyring_entry(host: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, host)
+        .map_err(|e| format!("Failed to open keyring entry for {}: {}", host, e))
+}
+
+fn load_password(host: &str) -> Option<String> {
+    match keyring_entry(host).and_then(|entry| {
+        entry
+            .get_password()
+            .map_err(|e| format!("Failed to read password for {}: {}", host, e))
+    }) {
+        Ok(password) => Some(password),
+        Err(e) => {
+            debug!("{}", e);
+            None
+        }
+    }
+}
+
+/// Saves the host's password to the keyring. The anonymous default is not a secret
+/// and only clears what was stored.
+fn store_password(host: &str, password: &str) -> Result<(), String> {
+    let entry = keyring_entry(host)?;
+    if password == default_password() {
+        return match entry.delete_credential() {
+            Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove password for {}: {}", host, e)),
+        };
+    }
+
+    entry
+        .set_password(password)
+        .map_err(|e| format!("Failed to save password for {}: {}", host, e))
+}
+
+/// Saved profiles, keyed by host, with their passwords filled in from the keyring.
+pub fn load_profiles() -> HashMap<String, FtpConnectionProfile> {
+    PROFILES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(read_profiles)
+        .clone()
+}
+
+fn read_profiles() -> HashMap<String, FtpConnectionProfile> {
+    let mut profiles: HashMap<String, FtpConnectionProfile> =
+        load_config_file(FTP_PROFILES_FILE).unwrap_or_default();
+
+    // Older versions wrote passwords into the file, move them to the keyring
+    let mut migrated = false;
+    for profile in profiles.values_mut() {
+        if profile.password.is_empty() {
+            profile.password = load_password(&profile.host).unwrap_or_else(default_password);
+        } else {
+            match store_password(&profile.host, &profile.password) {
+                Ok(_) => migrated = true,
+                Err(e) => warn!("{}", e),
+            }
+        }
+        profile.has_password = profile.password != default_password();
+    }
+
+    if migrated {
+        match save_config_file(FTP_PROFILES_FILE, &profiles) {
+            Ok(_) => info!("Moved saved FTP passwords to the system keyring"),
+            Err(e) => warn!("Failed to rewrite FTP profiles without passwords: {}", e),
+        }
+    }
+    profiles
+}
+
+/// The saved profile for a host, or the default DBI profile if none was configured.
+pub fn profile_for_host(host: &str) -> FtpConnectionProfile {
+    load_profiles()
+        .remove(host)
+        .unwrap_or_else(|| FtpConnectionProfile::anonymous(host))
+}
+
+/// Fills in the saved password when the frontend left it empty.
+pub fn with_saved_password(mut profile: FtpConnectionProfile) -> FtpConnectionProfile {
+    if profile.password.is_empty() {
+        profile.password = load_password(&profile.host).unwrap_or_else(default_password);
+    }
+    profile.has_password = profile.password != default_password();
+    profile
+}
+
+pub fn save_profile(profile: &FtpConnectionProfile) -> Result<(), String> {
+    if !profile.use_ftps && profile.username != default_username() {
+        warn!(
+            "FTP profile for {} sends credentials without TLS",
+            profile.address()
+        );
+    }
+
+    let profile = with_saved_password(profile.clone());
+    store_password(&profile.host, &profile.password)?;
+
+    let mut profiles = load_profiles();
+    profiles.insert(profile.host.clone(), profile.clone());
+    save_config_file(FTP_PROFILES_FILE, &profiles)?;
+    *PROFILES.lock().unwrap() = Some(profiles);
+    info!("Saved FTP profile for {}", profile.address());
+    Ok(())
+}
+
+pub fn remove_profile(host: &str) -> Result<(), String> {
+    let mut profiles = load_profiles();
+    if profiles.remove(host).is_none() {
+        return Err(format!("No saved FTP profile for {}", host));
+    }
+    save_config_file(FTP_PROFILES_FILE, &profiles)?;
+    *PROFILES.lock().unwrap() = Some(profiles);
+    store_password(host, &default_password())?;
+    info!("Removed FTP profile for {}", host);
+    Ok(())
+}
+
+/// Ports discovery should probe: the DBI default plus any port a saved profile uses.
+pub fn known_ports() -> Vec<u16> {
+    let mut ports = vec![DBI_FTP_PORT];
+    for profile in load_profiles().values() {
+        if !ports.contains(&profile.port) {
+            ports.push(profile.port);
+        }
+    }
+    ports
+}
+
+/// Saved profiles for the frontend. Passwords are left out, `has_password` says
+/// whether one is saved.
+#[tauri::command]
+pub fn get_saved_ftp_profiles() -> Vec<FtpConnectionProfile> {
+    load_profiles().into_values().collect()
+}
+
+#[tauri::command]
+pub fn remove_ftp_profile(host: String) -> Result<String, String> {
+    remove_profile(&host)?;
+    Ok(format!("FTP profile removed for {}", host))
+}
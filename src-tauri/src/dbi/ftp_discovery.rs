@@ -1,21 +1,146 @@
+use futures::stream::{self, StreamExt};
 use log::{debug, error, info, warn};
-use serde::Serialize;
-use std::collections::HashSet;
-use std::net::TcpStream;
+use network_interface::{Addr, NetworkInterface, NetworkInterfaceConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::TcpStream;
+use tokio_util::sync::CancellationToken;
+
+use super::device_registry::{DeviceRegistry, DeviceStatusPayload};
+use super::ftp_connection::{self, DBI_FTP_PORT};
+use super::ftp_manager::FTPManager;
+use crate::configs::storage::{load_config_file, save_config_file};
 
-const DBI_FTP_PORT: u16 = 5000;
 const TIMEOUT_MS: u64 = 1000;
-const THREAD_POOL_SIZE: usize = 100;
+const REPLY_TIMEOUT_MS: u64 = 1500;
+const MAX_REPLY_LINES: usize = 64;
+const MAX_CONCURRENT_PROBES: usize = 100;
 const RETRY_INTERVAL: u16 = 10;
-
-#[derive(Debug, Clone)]
+/// Upper bound of addresses taken from any one network or range.
+const MAX_SCAN_HOSTS: usize = 65_536;
+/// Interfaces on subnets wider than this are only scanned when `scan_large_subnets` is set.
+const MAX_DEFAULT_PREFIX: u8 = 22;
+/// Name prefixes of container, VM, VPN and tunnel adapters, which never lead to a console.
+const VIRTUAL_INTERFACE_PREFIXES: [&str; 18] = [
+    "docker",
+    "br-",
+    "veth",
+    "virbr",
+    "vmnet",
+    "vboxnet",
+    "vethernet",
+    "virtualbox",
+    "vmware",
+    "hyper-v",
+    "tun",
+    "tap",
+    "utun",
+    "wg",
+    "tailscale",
+    "zt",
+    "ppp",
+    "ipsec",
+];
+const DISCOVERY_SETTINGS_FILE: &str = ".ftp_discovery.json";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FoundService {
     pub ip_address: String,
+    pub port: u16,
+    pub server_kind: FtpServerKind,
+    pub banner: String,
+    pub latency_ms: u64,
+    /// From the neighbour table once the scan is done, None if it was not listed.
+    pub mac_address: Option<String>,
+}
+
+/// Which FTP server software answered, going by its greeting and SYST/FEAT replies.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FtpServerKind {
+    Dbi,
+    SysFtpd,
+    Ftpd,
+    Unknown,
+}
+
+impl FtpServerKind {
+    fn classify(banner: &str, details: &str) -> Self {
+        let text = format!("{}\n{}", banner, details).to_lowercase();
+
+        if text.contains("dbi") {
+            FtpServerKind::Dbi
+        } else if text.contains("sys-ftpd") || text.contains("sys_ftpd") || text.contains("sysftpd")
+        {
+            FtpServerKind::SysFtpd
+        } else if text.contains("ftpd") {
+            FtpServerKind::Ftpd
+        } else {
+            FtpServerKind::Unknown
+        }
+    }
+}
+
+/// An IPv4 address assigned to one of this machine's network interfaces.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalInterface {
+    pub name: String,
+    pub ip_address: String,
+    pub netmask: String,
+    pub prefix_len: u8,
+    pub cidr: String,
+    /// Loopback, point-to-point or a container/VM/VPN adapter. Skipped by default.
+    pub is_virtual: bool,
+}
+
+/// Which networks and ports the monitor scans, saved to `.ftp_discovery.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverySettings {
+    /// Interface names to scan. Empty means every usable interface.
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+    /// Extra CIDR ranges to scan, e.g. `10.0.0.0/22`.
+    #[serde(default)]
+    pub ranges: Vec<String>,
+    /// Ports to probe on every host. Empty means just the DBI port.
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// Send SYST and FEAT after the greeting to identify the server more reliably.
+    #[serde(default = "default_probe_features")]
+    pub probe_features: bool,
+    /// Also scan virtual interfaces that are not picked in `interfaces`.
+    #[serde(default)]
+    pub scan_virtual_interfaces: bool,
+    /// Also scan interfaces on subnets wider than a /22.
+    #[serde(default)]
+    pub scan_large_subnets: bool,
+}
+
+impl Default for DiscoverySettings {
+    fn default() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            ranges: Vec::new(),
+            ports: Vec::new(),
+            probe_features: default_probe_features(),
+            scan_virtual_interfaces: false,
+            scan_large_subnets: false,
+        }
+    }
+}
+
+fn default_probe_features() -> bool {
+    true
 }
 
 #[derive(Debug)]
@@ -43,176 +168,522 @@ impl FTPDiscovery {
         FTPDiscovery
     }
 
-    pub fn scan(&self, ip_address: &str) -> Option<FoundService> {
-        let target_address = format!("{}:{}", ip_address, DBI_FTP_PORT);
-        info!("Scanning FTP service at {}", target_address);
+    pub async fn scan(&self, ip_address: &str) -> Option<FoundService> {
+        self.scan_port(ip_address, DBI_FTP_PORT, true).await
+    }
 
-        match TcpStream::connect_timeout(
-            &target_address.parse().ok()?,
-            Duration::from_millis(TIMEOUT_MS),
-        ) {
-            Ok(_) => {
-                info!("✓ Found FTP service at {}", ip_address);
-                Some(FoundService {
-                    ip_address: ip_address.to_string(),
-                })
+    /// Connects to `ip_address:port` and only reports a service if it greets us like an
+    /// FTP server. With `probe_features` set, SYST and FEAT are also sent to help tell
+    /// the console FTP servers apart.
+    pub async fn scan_port(
+        &self,
+        ip_address: &str,
+        port: u16,
+        probe_features: bool,
+    ) -> Option<FoundService> {
+        let target_address = format!("{}:{}", ip_address, port);
+        debug!("Scanning FTP service at {}", target_address);
+
+        let addr: SocketAddr = match target_address.parse() {
+            Ok(addr) => addr,
+            Err(_) => {
+                warn!("Invalid address format: {}", target_address);
+                return None;
             }
-            Err(e) => {
-                debug!("No FTP service at {}: {}", ip_address, e);
-                None
+        };
+
+        let started = Instant::now();
+        let stream =
+            match tokio::time::timeout(Duration::from_millis(TIMEOUT_MS), TcpStream::connect(addr))
+                .await
+            {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    debug!("No FTP service at {}: {}", target_address, e);
+                    return None;
+                }
+                Err(_) => {
+                    debug!("No FTP service at {}: timed out", target_address);
+                    return None;
+                }
+            };
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let (read_half, mut write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+
+        // Anything that does not open with a 220 greeting is not an FTP server
+        let banner = match read_reply(&mut reader).await {
+            Some((220, text)) => text,
+            Some((code, _)) => {
+                debug!(
+                    "Service at {} answered with {} instead of an FTP greeting",
+                    target_address, code
+                );
+                return None;
+            }
+            None => {
+                debug!("Service at {} sent no FTP greeting", target_address);
+                return None;
+            }
+        };
+
+        let mut details = String::new();
+        if probe_features {
+            for command in ["SYST", "FEAT"] {
+                if write_half
+                    .write_all(format!("{}\r\n", command).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                match read_reply(&mut reader).await {
+                    Some((_, text)) => {
+                        details.push('\n');
+                        details.push_str(&text);
+                    }
+                    None => break,
+                }
             }
         }
+        let _ = write_half.write_all(b"QUIT\r\n").await;
+
+        let server_kind = FtpServerKind::classify(&banner, &details);
+        info!(
+            "✓ Found FTP service at {} ({:?}, {}ms)",
+            target_address, server_kind, latency_ms
+        );
+
+        Some(FoundService {
+            ip_address: ip_address.to_string(),
+            port,
+            server_kind,
+            banner,
+            latency_ms,
+            mac_address: None,
+        })
     }
 
-    pub fn scan_all(&self) -> FoundServices {
+    /// Scans every selected local interface and extra range. `on_found` is called for
+    /// each host as soon as it answers.
+    pub async fn scan_all<F>(&self, cancel: &CancellationToken, on_found: F) -> FoundServices
+    where
+        F: FnMut(&FoundService),
+    {
         info!("Scanning all FTP services on the network...");
 
-        // Get local IP to determine network range
-        if let Some(local_ip) = self.get_local_ip() {
-            info!("Detected local IP: {}", local_ip);
-            let base_ip = self.get_base_ip(&local_ip);
-            info!("Scanning network range: {}.1 to {}.254", base_ip, base_ip);
-            self.scan_range(&format!("{}.1", base_ip), &format!("{}.254", base_ip))
-        } else {
-            error!("Could not determine local IP address");
-            FoundServices::new()
+        let settings = load_discovery_settings();
+        let ips = self.scan_targets(&settings);
+
+        if ips.is_empty() {
+            error!("No local interfaces or ranges to scan");
+            return FoundServices::new();
         }
+
+        self.scan_addresses(ips, cancel, on_found).await
     }
 
-    pub fn scan_range(&self, start_ip: &str, end_ip: &str) -> FoundServices {
+    pub async fn scan_range<F>(
+        &self,
+        start_ip: &str,
+        end_ip: &str,
+        cancel: &CancellationToken,
+        on_found: F,
+    ) -> FoundServices
+    where
+        F: FnMut(&FoundService),
+    {
         info!("Scanning FTP services from {} to {}...", start_ip, end_ip);
 
-        let found_services = Arc::new(Mutex::new(FoundServices::new()));
-
         let ips = self.generate_ip_range(start_ip, end_ip);
+        self.scan_addresses(ips, cancel, on_found).await
+    }
+
+    /// Probes every address with at most `MAX_CONCURRENT_PROBES` connects in flight.
+    /// Returns early with whatever was found if `cancel` fires.
+    async fn scan_addresses<F>(
+        &self,
+        ips: Vec<String>,
+        cancel: &CancellationToken,
+        mut on_found: F,
+    ) -> FoundServices
+    where
+        F: FnMut(&FoundService),
+    {
+        let mut found_services = FoundServices::new();
+
+        // Probe the configured ports plus any custom port from a saved connection profile
+        let settings = load_discovery_settings();
+        let mut ports = if settings.ports.is_empty() {
+            vec![DBI_FTP_PORT]
+        } else {
+            settings.ports.clone()
+        };
+        for port in ftp_connection::known_ports() {
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+        let probe_features = settings.probe_features;
 
-        info!("Generated {} IP addresses to scan", ips.len());
+        info!("Scanning {} IP addresses on port(s) {:?}", ips.len(), ports);
 
-        let chunks: Vec<Vec<String>> = ips
-            .chunks(THREAD_POOL_SIZE)
-            .map(|chunk| chunk.to_vec())
+        let targets: Vec<(String, u16)> = ips
+            .iter()
+            .flat_map(|ip| ports.iter().map(move |port| (ip.clone(), *port)))
             .collect();
 
-        debug!(
-            "Split into {} chunks of up to {} IPs each",
-            chunks.len(),
-            THREAD_POOL_SIZE
-        );
+        let mut probes = stream::iter(targets)
+            .map(|(ip, port)| async move { self.scan_port(&ip, port, probe_features).await })
+            .buffer_unordered(MAX_CONCURRENT_PROBES);
 
-        for (chunk_idx, chunk) in chunks.iter().enumerate() {
-            debug!("Processing chunk {} of {}", chunk_idx + 1, chunks.len());
-            let mut handles = vec![];
-
-            for ip in chunk {
-                let found_services = Arc::clone(&found_services);
-                let ip_clone = ip.clone();
-
-                let handle = thread::spawn(move || {
-                    let target_address = format!("{}:{}", ip_clone, DBI_FTP_PORT);
-
-                    if let Ok(addr) = target_address.parse() {
-                        match TcpStream::connect_timeout(&addr, Duration::from_millis(TIMEOUT_MS)) {
-                            Ok(_) => {
-                                // Service found - add to collection
-                                let mut services = found_services.lock().unwrap();
-                                services.add(FoundService {
-                                    ip_address: ip_clone.clone(),
-                                });
-                                info!("✓ Found FTP service at {}", ip_clone);
-                            }
-                            Err(e) => {
-                                debug!("No FTP service at {}: {}", ip_clone, e);
-                            }
-                        }
-                    } else {
-                        warn!("Invalid address format: {}", target_address);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    info!("Scan cancelled");
+                    break;
+                }
+                next = probes.next() => match next {
+                    Some(Some(service)) => {
+                        on_found(&service);
+                        found_services.add(service);
                     }
-                });
-                handles.push(handle);
+                    Some(None) => {}
+                    None => break,
+                },
             }
+        }
 
-            for handle in handles {
-                if let Err(e) = handle.join() {
-                    error!("Thread panicked: {:?}", e);
-                }
+        // Hosts that just answered are in the neighbour table now
+        if !found_services.services.is_empty() {
+            let macs = tokio::task::spawn_blocking(neighbour_macs)
+                .await
+                .unwrap_or_default();
+            for service in found_services.services.iter_mut() {
+                service.mac_address = macs.get(&service.ip_address).cloned();
             }
         }
 
-        let services = found_services.lock().unwrap();
         info!(
             "Scan complete. Found {} FTP services.",
-            services.services.len()
+            found_services.services.len()
         );
 
-        // Return owned data
-        FoundServices {
-            services: services.services.clone(),
-        }
+        found_services
     }
 
-    fn get_local_ip(&self) -> Option<String> {
-        use std::net::UdpSocket;
+    /// Lists the usable IPv4 interfaces on this machine with their real netmasks.
+    /// Works without any internet route, unlike asking the OS which address reaches 8.8.8.8.
+    pub fn list_local_interfaces(&self) -> Vec<LocalInterface> {
+        let interfaces = match NetworkInterface::show() {
+            Ok(interfaces) => interfaces,
+            Err(e) => {
+                error!("Failed to enumerate network interfaces: {}", e);
+                return vec![];
+            }
+        };
+
+        let mut local_interfaces = Vec::new();
 
-        debug!("Attempting to determine local IP address");
+        for interface in interfaces {
+            for addr in &interface.addr {
+                let Addr::V4(v4) = addr else {
+                    continue;
+                };
 
-        // Connect to an external address to determine which local IP is used
-        let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
-        socket.connect("8.8.8.8:80").ok()?;
-        let local_ip = socket.local_addr().ok().map(|addr| addr.ip().to_string());
+                if v4.ip.is_loopback() || v4.ip.is_link_local() || v4.ip.is_unspecified() {
+                    continue;
+                }
 
-        if let Some(ref ip) = local_ip {
-            debug!("Local IP determined: {}", ip);
-        } else {
-            warn!("Failed to determine local IP");
+                let netmask = v4.netmask.unwrap_or_else(|| {
+                    warn!(
+                        "No netmask reported for {} on {}, assuming /24",
+                        v4.ip, interface.name
+                    );
+                    Ipv4Addr::new(255, 255, 255, 0)
+                });
+                let prefix_len = u32::from(netmask).count_ones() as u8;
+                let network = Ipv4Addr::from(u32::from(v4.ip) & u32::from(netmask));
+
+                // Tunnels have no hardware address and point-to-point links a /31 or /32
+                let has_mac = interface
+                    .mac_addr
+                    .as_deref()
+                    .is_some_and(|mac| !mac.is_empty() && mac != "00:00:00:00:00:00");
+                let name = interface.name.to_lowercase();
+                let is_virtual = !has_mac
+                    || prefix_len >= 31
+                    || VIRTUAL_INTERFACE_PREFIXES
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix));
+
+                local_interfaces.push(LocalInterface {
+                    name: interface.name.clone(),
+                    ip_address: v4.ip.to_string(),
+                    netmask: netmask.to_string(),
+                    prefix_len,
+                    cidr: format!("{}/{}", network, prefix_len),
+                    is_virtual,
+                });
+            }
         }
 
-        local_ip
+        debug!("Found {} usable local interfaces", local_interfaces.len());
+        local_interfaces
     }
 
-    fn get_base_ip(&self, ip: &str) -> String {
-        let parts: Vec<&str> = ip.split('.').collect();
-        if parts.len() >= 3 {
-            let base = format!("{}.{}.{}", parts[0], parts[1], parts[2]);
-            debug!("Base IP extracted: {}", base);
-            base
-        } else {
-            warn!("Invalid IP format, using default: 192.168.1");
-            "192.168.1".to_string()
+    /// Resolves the discovery settings into the list of addresses to probe, smallest
+    /// network first so the home LAN is covered before any wide range.
+    fn scan_targets(&self, settings: &DiscoverySettings) -> Vec<String> {
+        let mut networks: Vec<(Ipv4Addr, u8)> = Vec::new();
+
+        for interface in self.list_local_interfaces() {
+            // Picking an interface by name opts it in, whatever kind it is
+            let picked = settings.interfaces.contains(&interface.name);
+            if !settings.interfaces.is_empty() && !picked {
+                continue;
+            }
+            if interface.is_virtual && !picked && !settings.scan_virtual_interfaces {
+                debug!(
+                    "Skipping virtual interface {} ({})",
+                    interface.name, interface.cidr
+                );
+                continue;
+            }
+            if interface.prefix_len < MAX_DEFAULT_PREFIX && !settings.scan_large_subnets {
+                info!(
+                    "Skipping interface {} ({}), subnets wider than /{} are not scanned by default",
+                    interface.name, interface.cidr, MAX_DEFAULT_PREFIX
+                );
+                continue;
+            }
+
+            info!("Scanning interface {} ({})", interface.name, interface.cidr);
+            if let Ok(network) = parse_cidr(&interface.cidr) {
+                networks.push(network);
+            }
+        }
+
+        for range in &settings.ranges {
+            match parse_cidr(range) {
+                Ok(network) => {
+                    info!("Scanning configured range {}", range);
+                    networks.push(network);
+                }
+                Err(e) => warn!("Skipping invalid scan range: {}", e),
+            }
+        }
+
+        networks.sort_by_key(|(_, prefix_len)| std::cmp::Reverse(*prefix_len));
+
+        // Several interfaces can sit on the same subnet, so dedupe the addresses
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut addresses: Vec<u32> = Vec::new();
+        for (network, prefix_len) in networks {
+            let (start, end) = host_bounds(network, prefix_len);
+            let hosts = (end as u64).saturating_sub(start as u64) + 1;
+            if hosts > MAX_SCAN_HOSTS as u64 {
+                warn!(
+                    "Scanning only the first {} of {} addresses in {}/{}, narrow the range",
+                    MAX_SCAN_HOSTS, hosts, network, prefix_len
+                );
+            }
+
+            for ip in (start..=end).take(MAX_SCAN_HOSTS) {
+                if seen.insert(ip) {
+                    addresses.push(ip);
+                }
+            }
         }
+
+        addresses
+            .into_iter()
+            .map(|ip| Ipv4Addr::from(ip).to_string())
+            .collect()
     }
 
     fn generate_ip_range(&self, start_ip: &str, end_ip: &str) -> Vec<String> {
-        let start_parts: Vec<u8> = start_ip.split('.').filter_map(|s| s.parse().ok()).collect();
-        let end_parts: Vec<u8> = end_ip.split('.').filter_map(|s| s.parse().ok()).collect();
+        let (start, end) = match (start_ip.parse::<Ipv4Addr>(), end_ip.parse::<Ipv4Addr>()) {
+            (Ok(start), Ok(end)) => (u32::from(start), u32::from(end)),
+            _ => {
+                error!("Invalid IP range format: {} to {}", start_ip, end_ip);
+                return vec![];
+            }
+        };
 
-        if start_parts.len() != 4 || end_parts.len() != 4 {
-            error!("Invalid IP range format: {} to {}", start_ip, end_ip);
+        if start > end {
+            error!(
+                "Invalid IP range, start is after end: {} to {}",
+                start_ip, end_ip
+            );
             return vec![];
         }
 
-        let mut ips = Vec::new();
-        let base = format!("{}.{}.{}", start_parts[0], start_parts[1], start_parts[2]);
-
-        // Generate IPs by varying the last octet
-        for i in start_parts[3]..=end_parts[3] {
-            ips.push(format!("{}.{}", base, i));
-        }
+        // Walk the whole 32-bit address so ranges can cross octet boundaries
+        let ips: Vec<String> = (start..=end)
+            .take(MAX_SCAN_HOSTS)
+            .map(|ip| Ipv4Addr::from(ip).to_string())
+            .collect();
 
         debug!("Generated {} IP addresses in range", ips.len());
         ips
     }
 }
 
+/// MAC addresses from the OS neighbour (ARP) table, keyed by IPv4 address.
+fn neighbour_macs() -> HashMap<String, String> {
+    #[cfg(target_os = "linux")]
+    let table = std::fs::read_to_string("/proc/net/arp").unwrap_or_default();
+
+    #[cfg(not(target_os = "linux"))]
+    let table = {
+        let mut command = std::process::Command::new("arp");
+        command.arg("-a");
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+        command
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    };
+
+    // Every platform prints one host per line with the IP and MAC among its columns
+    let mut macs = HashMap::new();
+    for line in table.lines() {
+        let tokens: Vec<&str> = line
+            .split_whitespace()
+            .map(|token| token.trim_matches(|c| c == '(' || c == ')'))
+            .collect();
+        let ip = tokens
+            .iter()
+            .find(|token| token.parse::<Ipv4Addr>().is_ok());
+        let mac = tokens.iter().find_map(|token| normalize_mac(token));
+        if let (Some(ip), Some(mac)) = (ip, mac) {
+            macs.insert(ip.to_string(), mac);
+        }
+    }
+
+    debug!("Read {} entries from the neighbour table", macs.len());
+    macs
+}
+
+/// Lower-case, colon-separated, zero-padded form of a MAC address, as `arp` prints them
+/// differently on each OS. None for anything else and for incomplete entries.
+fn normalize_mac(token: &str) -> Option<String> {
+    let parts: Vec<&str> = token.split(|c| c == ':' || c == '-').collect();
+    if parts.len() != 6
+        || parts.iter().any(|part| {
+            part.is_empty() || part.len() > 2 || !part.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    {
+        return None;
+    }
+
+    let mac = parts
+        .iter()
+        .map(|part| format!("{:0>2}", part.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(":");
+    (mac != "00:00:00:00:00:00" && mac != "ff:ff:ff:ff:ff:ff").then_some(mac)
+}
+
+/// Reads one (possibly multi-line) FTP reply, returning its code and text.
+async fn read_reply(reader: &mut BufReader<OwnedReadHalf>) -> Option<(u16, String)> {
+    let mut lines: Vec<String> = Vec::new();
+
+    loop {
+        let mut line = String::new();
+        let read = tokio::time::timeout(
+            Duration::from_millis(REPLY_TIMEOUT_MS),
+            reader.read_line(&mut line),
+        )
+        .await
+        .ok()?
+        .ok()?;
+
+        if read == 0 {
+            break;
+        }
+
+        let line = line.trim_end().to_string();
+
+        // The last line of a reply is "ddd text"; continuation lines use "ddd-" or no code
+        let bytes = line.as_bytes();
+        let is_last = bytes.len() >= 3
+            && bytes[..3].iter().all(u8::is_ascii_digit)
+            && bytes.get(3) != Some(&b'-');
+
+        lines.push(line);
+
+        if is_last || lines.len() >= MAX_REPLY_LINES {
+            break;
+        }
+    }
+
+    let code = lines.first()?.get(..3)?.parse().ok()?;
+    Some((code, lines.join("\n")))
+}
+
+/// Parses `a.b.c.d/len` into the network address and prefix length.
+fn parse_cidr(cidr: &str) -> Result<(Ipv4Addr, u8), String> {
+    let (ip, prefix_len) = cidr
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| format!("Missing prefix length in {}", cidr))?;
+
+    let ip: Ipv4Addr = ip
+        .parse()
+        .map_err(|e| format!("Invalid address in {}: {}", cidr, e))?;
+    let prefix_len: u8 = prefix_len
+        .parse()
+        .map_err(|e| format!("Invalid prefix length in {}: {}", cidr, e))?;
+
+    if prefix_len > 32 {
+        return Err(format!("Prefix length out of range in {}", cidr));
+    }
+
+    Ok((ip, prefix_len))
+}
+
+/// First and last host address of a network, skipping the network and broadcast
+/// addresses where the subnet is big enough to have them.
+fn host_bounds(network: Ipv4Addr, prefix_len: u8) -> (u32, u32) {
+    let mask = if prefix_len == 0 {
+        0
+    } else {
+        u32::MAX << (32 - prefix_len as u32)
+    };
+    let first = u32::from(network) & mask;
+    let last = first | !mask;
+
+    if prefix_len >= 31 {
+        (first, last)
+    } else {
+        (first + 1, last - 1)
+    }
+}
+
+pub fn load_discovery_settings() -> DiscoverySettings {
+    load_config_file(DISCOVERY_SETTINGS_FILE).unwrap_or_default()
+}
+
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct FTPStatusPayload {
     is_active: bool,
     active_count: usize,
     active_ips: Vec<String>,
+    services: Vec<FoundService>,
 }
 
 pub struct FTPMonitor {
     is_running: Arc<AtomicBool>,
+    cancel_token: Mutex<Option<CancellationToken>>,
     app_handle: AppHandle,
 }
 
@@ -220,6 +691,7 @@ impl FTPMonitor {
     pub fn new(app_handle: AppHandle) -> Self {
         Self {
             is_running: Arc::new(AtomicBool::new(false)),
+            cancel_token: Mutex::new(None),
             app_handle,
         }
     }
@@ -232,15 +704,24 @@ impl FTPMonitor {
 
         self.is_running.store(true, Ordering::SeqCst);
 
-        let is_running = Arc::clone(&self.is_running);
+        // Each run gets a fresh token; a cancelled token cannot be reused
+        let cancel = CancellationToken::new();
+        *self.cancel_token.lock().unwrap() = Some(cancel.clone());
+
         let app_handle = self.app_handle.clone();
 
-        thread::spawn(move || {
+        tauri::async_runtime::spawn(async move {
             let discovery = FTPDiscovery::new();
-            let mut previous_services: HashSet<String> = HashSet::new();
+            let registry = match app_handle.try_state::<Arc<DeviceRegistry>>() {
+                Some(registry) => registry.inner().clone(),
+                None => {
+                    error!("Device registry not initialized, monitor not started");
+                    return;
+                }
+            };
             let mut iteration = 0;
 
-            while is_running.load(Ordering::SeqCst) {
+            while !cancel.is_cancelled() {
                 iteration += 1;
 
                 info!(
@@ -250,37 +731,66 @@ impl FTPMonitor {
                     chrono::Local::now().format("%Y-%m-%d")
                 );
 
-                // Perform scan
-                let found_services = discovery.scan_all();
+                // Perform scan, reporting each host as soon as it answers
+                let found_services = discovery
+                    .scan_all(&cancel, |service| {
+                        if let Err(e) = app_handle.emit("ftp-service-found", service) {
+                            error!("Failed to emit FTP service event: {}", e);
+                        }
+                    })
+                    .await;
+
+                // A partial scan would make every console look like it went offline
+                if cancel.is_cancelled() {
+                    break;
+                }
+
                 let current_services: HashSet<String> = found_services
                     .services
                     .iter()
                     .map(|s| s.ip_address.clone())
                     .collect();
 
-                // Detect changes
-                let new_services: Vec<_> =
-                    current_services.difference(&previous_services).collect();
-
-                let removed_services: Vec<_> =
-                    previous_services.difference(&current_services).collect();
+                // Match the results against the device registry to see who came and went
+                let changes = registry.update_from_scan(&found_services.services);
+                let (came_online, went_offline) = (changes.came_online, changes.went_offline);
 
                 // Report changes
-                if !new_services.is_empty() {
-                    info!("  🟢 NEW services detected:");
-                    for ip in &new_services {
-                        info!("     + {}", ip);
+                if !came_online.is_empty() {
+                    info!("  🟢 Consoles ONLINE:");
+                    for device in &came_online {
+                        info!(
+                            "     + {} ({}:{})",
+                            device.name, device.last_ip, device.port
+                        );
+                        let _ = app_handle.emit(
+                            "device-online",
+                            DeviceStatusPayload {
+                                device: device.clone(),
+                                online: true,
+                            },
+                        );
                     }
                 }
 
-                if !removed_services.is_empty() {
-                    error!("  🔴 Services OFFLINE:");
-                    for ip in &removed_services {
-                        error!("     - {}", ip);
+                if !went_offline.is_empty() {
+                    error!("  🔴 Consoles OFFLINE:");
+                    for device in &went_offline {
+                        error!(
+                            "     - {} ({}:{})",
+                            device.name, device.last_ip, device.port
+                        );
+                        let _ = app_handle.emit(
+                            "device-offline",
+                            DeviceStatusPayload {
+                                device: device.clone(),
+                                online: false,
+                            },
+                        );
                     }
                 }
 
-                if new_services.is_empty() && removed_services.is_empty() {
+                if came_online.is_empty() && went_offline.is_empty() {
                     info!("No changes detected");
                 }
 
@@ -294,6 +804,7 @@ impl FTPMonitor {
                     is_active,
                     active_count: current_services.len(),
                     active_ips: active_ips.clone(),
+                    services: found_services.services.clone(),
                 };
 
                 if let Err(e) = app_handle.emit("ftp-status-changed", &payload) {
@@ -304,12 +815,31 @@ impl FTPMonitor {
                     info!("Active IPs: {}", active_ips.join(", "));
                 }
 
-                // Update previous state
-                previous_services = current_services;
+                for merge in &changes.merged {
+                    let _ = app_handle.emit("device-merged", merge);
+                }
+
+                // Point the transfer manager at the preferred console and resume its queue
+                if !came_online.is_empty() || !went_offline.is_empty() || !changes.merged.is_empty()
+                {
+                    if let Some(manager_state) =
+                        app_handle.try_state::<Arc<parking_lot::Mutex<Option<FTPManager>>>>()
+                    {
+                        if let Some(manager) = manager_state.lock().as_ref() {
+                            for merge in &changes.merged {
+                                manager.reassign_device(&merge.from, Some(&merge.into));
+                            }
+                            manager.handle_devices_changed(&registry);
+                        }
+                    }
+                }
 
-                // Wait before next scan
+                // Wait before next scan, waking up immediately if the monitor is stopped
                 info!("Waiting {} seconds until next scan.", scan_interval_seconds);
-                thread::sleep(Duration::from_secs(scan_interval_seconds));
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_secs(scan_interval_seconds)) => {}
+                }
             }
 
             error!("Monitor stopped. Total scans performed: {}", iteration);
@@ -318,6 +848,9 @@ impl FTPMonitor {
 
     pub fn stop(&self) {
         self.is_running.store(false, Ordering::SeqCst);
+        if let Some(cancel) = self.cancel_token.lock().unwrap().take() {
+            cancel.cancel();
+        }
         info!("FTP Monitor stop requested");
     }
 
@@ -371,3 +904,167 @@ pub fn is_ftp_monitor_running(
     let monitor_guard = state.lock();
     monitor_guard.as_ref().map_or(false, |m| m.is_running())
 }
+
+#[tauri::command]
+pub fn get_local_interfaces() -> Vec<LocalInterface> {
+    FTPDiscovery::new().list_local_interfaces()
+}
+
+#[tauri::command]
+pub fn get_discovery_settings() -> DiscoverySettings {
+    load_discovery_settings()
+}
+
+#[tauri::command]
+pub fn set_discovery_settings(settings: DiscoverySettings) -> Result<String, String> {
+    for range in &settings.ranges {
+        parse_cidr(range)?;
+    }
+
+    save_config_file(DISCOVERY_SETTINGS_FILE, &settings)?;
+    info!(
+        "Discovery settings saved: {} interface(s), {} range(s)",
+        settings.interfaces.len(),
+        settings.ranges.len()
+    );
+    Ok("Discovery settings saved".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cidr_reads_address_and_prefix() {
+        assert_eq!(
+            parse_cidr(" 192.168.1.0/24 "),
+            Ok((Ipv4Addr::new(192, 168, 1, 0), 24))
+        );
+        assert_eq!(parse_cidr("0.0.0.0/0"), Ok((Ipv4Addr::UNSPECIFIED, 0)));
+        assert_eq!(
+            parse_cidr("10.0.0.7/32"),
+            Ok((Ipv4Addr::new(10, 0, 0, 7), 32))
+        );
+    }
+
+    #[test]
+    fn parse_cidr_rejects_bad_input() {
+        assert!(parse_cidr("192.168.1.0").is_err());
+        assert!(parse_cidr("192.168.1/24").is_err());
+        assert!(parse_cidr("192.168.1.0/33").is_err());
+        assert!(parse_cidr("192.168.1.0/abc").is_err());
+    }
+
+    #[test]
+    fn host_bounds_skips_network_and_broadcast() {
+        let (first, last) = host_bounds(Ipv4Addr::new(192, 168, 1, 77), 24);
+        assert_eq!(Ipv4Addr::from(first), Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(Ipv4Addr::from(last), Ipv4Addr::new(192, 168, 1, 254));
+    }
+
+    #[test]
+    fn host_bounds_handles_prefix_edges() {
+        let (first, last) = host_bounds(Ipv4Addr::new(10, 1, 2, 3), 0);
+        assert_eq!((first, last), (1, u32::MAX - 1));
+
+        let (first, last) = host_bounds(Ipv4Addr::new(10, 1, 2, 2), 31);
+        assert_eq!(Ipv4Addr::from(first), Ipv4Addr::new(10, 1, 2, 2));
+        assert_eq!(Ipv4Addr::from(last), Ipv4Addr::new(10, 1, 2, 3));
+
+        let (first, last) = host_bounds(Ipv4Addr::new(10, 1, 2, 3), 32);
+        assert_eq!(first, last);
+        assert_eq!(Ipv4Addr::from(first), Ipv4Addr::new(10, 1, 2, 3));
+    }
+
+    #[test]
+    fn normalize_mac_accepts_common_forms() {
+        assert_eq!(
+            normalize_mac("98:B6:E9:0A:1B:2C").as_deref(),
+            Some("98:b6:e9:0a:1b:2c")
+        );
+        assert_eq!(
+            normalize_mac("98-b6-e9-0a-1b-2c").as_deref(),
+            Some("98:b6:e9:0a:1b:2c")
+        );
+        // macOS leaves out leading zeros
+        assert_eq!(
+            normalize_mac("98:b6:e9:a:1b:2c").as_deref(),
+            Some("98:b6:e9:0a:1b:2c")
+        );
+    }
+
+    #[test]
+    fn normalize_mac_rejects_placeholders_and_garbage() {
+        assert_eq!(normalize_mac("00:00:00:00:00:00"), None);
+        assert_eq!(normalize_mac("ff:ff:ff:ff:ff:ff"), None);
+        assert_eq!(normalize_mac("(incomplete)"), None);
+        assert_eq!(normalize_mac("98:b6:e9:0a:1b"), None);
+        assert_eq!(normalize_mac("98:b6:e9:0a:1b:2g"), None);
+        assert_eq!(normalize_mac("98:b6:e9:0a:1b:2c3"), None);
+    }
+
+    #[test]
+    fn classify_recognizes_server_software() {
+        assert_eq!(
+            FtpServerKind::classify("220 DBI FTP server ready", ""),
+            FtpServerKind::Dbi
+        );
+        assert_eq!(
+            FtpServerKind::classify("220 Hello", "215 UNIX Type: L8\nsys-ftpd 1.0"),
+            FtpServerKind::SysFtpd
+        );
+        assert_eq!(
+            FtpServerKind::classify("220 sys_ftpd ready", ""),
+            FtpServerKind::SysFtpd
+        );
+        assert_eq!(
+            FtpServerKind::classify("220 ftpd ready", ""),
+            FtpServerKind::Ftpd
+        );
+        assert_eq!(
+            FtpServerKind::classify("220 vsFTPd 3.0.3", ""),
+            FtpServerKind::Ftpd
+        );
+        assert_eq!(
+            FtpServerKind::classify("220 FileZilla Server", "211 End"),
+            FtpServerKind::Unknown
+        );
+    }
+
+    async fn reply_from(sent: &'static str) -> Option<(u16, String)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(sent.as_bytes()).await.unwrap();
+        });
+
+        let (read_half, _write_half) = TcpStream::connect(addr).await.unwrap().into_split();
+        let mut reader = BufReader::new(read_half);
+        let reply = read_reply(&mut reader).await;
+        server.await.unwrap();
+        reply
+    }
+
+    #[tokio::test]
+    async fn read_reply_reads_a_single_line() {
+        assert_eq!(
+            reply_from("220 DBI FTP server\r\n").await,
+            Some((220, "220 DBI FTP server".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_reply_reads_a_multi_line_reply() {
+        assert_eq!(
+            reply_from("211-Features:\r\n UTF8\r\n MLST\r\n211 End\r\n220 next\r\n").await,
+            Some((211, "211-Features:\n UTF8\n MLST\n211 End".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_reply_fails_without_a_code() {
+        assert_eq!(reply_from("hello\r\n").await, None);
+        assert_eq!(reply_from("").await, None);
+    }
+}
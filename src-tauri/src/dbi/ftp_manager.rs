@@ -1,20 +1,40 @@
 use crate::configs::defaults::get_game_path;
+use crate::configs::storage::{load_config_file, save_config_file};
+use crate::dbi::device_registry::DeviceRegistry;
+use crate::dbi::ftp_browser::{self, RemoteEntryKind};
+use crate::dbi::ftp_connection::{self, FtpConnection, FtpConnectionProfile, TransferError};
+use crate::dbi::local_lock::{self, LocalFileLock};
+use crate::torrent::state::TorrentState;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fs;
-use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter, Manager};
+use std::time::{Duration, Instant};
+use suppaftp::Status;
+use tauri::{AppHandle, Emitter, Listener, Manager};
 
-const DBI_FTP_PORT: u16 = 5000;
-const FTP_TIMEOUT_MS: u64 = 5000;
 const TRANSFER_THROUGHPUT_BUFFER: u64 = 2048;
+const PROGRESS_EMIT_INTERVAL_MS: u64 = 250;
+const FTP_QUEUE_FILE: &str = ".ftp_queue.json";
+const QUEUE_AUTO_SAVE_INTERVAL_SECS: u64 = 5;
+const FTP_SETTINGS_FILE: &str = ".ftp_settings.json";
+const FTP_AUTO_INSTALLED_FILE: &str = ".ftp_auto_installed.json";
+const RATE_LIMIT_WINDOW_SECS: u64 = 2;
+const MIN_THROTTLED_CHUNK: usize = 16 * 1024;
+
+// Values for a worker's transfer control flag
+const CONTROL_RUN: u8 = 0;
+const CONTROL_PAUSE: u8 = 1;
+const CONTROL_CANCEL: u8 = 2;
+
+const DIAGNOSTIC_TCP_SAMPLES: u32 = 3;
+const DEFAULT_DIAGNOSTIC_SIZE_MB: u64 = 16;
+const MAX_DIAGNOSTIC_SIZE_MB: u64 = 256;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +44,94 @@ pub struct GameFile {
     pub file_path: String,
     pub file_name: String,
     pub file_size: u64,
+    /// Where DBI should install the file. None uploads into the FTP root as-is.
+    #[serde(default)]
+    pub install_target: Option<InstallTarget>,
+    /// Set by the scanner when the file cannot be sent yet, e.g. while it is still downloading.
+    #[serde(default)]
+    pub unavailable_reason: Option<String>,
+}
+
+/// What a file in a game folder contains, in the order DBI should install them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Base,
+    Update,
+    Dlc,
+}
+
+impl ContentKind {
+    /// Classifies a file from the title id in its name, e.g. `[0100ABCD12340000]`.
+    /// Base games end in 000 and updates in 800; everything else under the game is DLC.
+    /// Files without a title id fall back to keywords and are otherwise treated as base.
+    pub fn from_file_name(file_name: &str) -> Self {
+        let title_id = file_name
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .find(|token| token.len() == 16 && token.starts_with("01"));
+
+        if let Some(title_id) = title_id {
+            return match &title_id[13..] {
+                "000" => ContentKind::Base,
+                "800" => ContentKind::Update,
+                _ => ContentKind::Dlc,
+            };
+        }
+
+        let lower = file_name.to_lowercase();
+        if lower.contains("update") || lower.contains("[upd]") {
+            ContentKind::Update
+        } else if lower.contains("dlc") {
+            ContentKind::Dlc
+        } else {
+            ContentKind::Base
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFile {
+    pub kind: ContentKind,
+    pub game_file: GameFile,
+}
+
+/// Every installable file of one game, already in install order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameBundle {
+    pub game_id: u32,
+    pub game_title: String,
+    pub total_size: u64,
+    pub files: Vec<BundleFile>,
+}
+
+/// Ties a queued task to the bundle it belongs to, so progress can be summed up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleMembership {
+    pub bundle_id: String,
+    pub kind: ContentKind,
+    pub total_files: usize,
+    pub total_bytes: u64,
+}
+
+/// Install destinations DBI exposes as virtual folders on its FTP server.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallTarget {
+    Sd,
+    Nand,
+}
+
+impl InstallTarget {
+    /// Name of the virtual folder in DBI's FTP root, minus its menu number prefix.
+    fn folder_name(&self) -> &'static str {
+        match self {
+            InstallTarget::Sd => "SD Card install",
+            InstallTarget::Nand => "NAND install",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,74 +145,634 @@ pub struct TransferProgress {
     pub transfer_speed: f64, // bytes per second
     pub eta_seconds: u64,
     pub status: TransferStatus,
+    /// Which attempt at this file the event belongs to, starting at 1.
+    pub attempt: u32,
+    /// Registered console the file is going to, None for the current target.
+    pub device_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum TransferStatus {
     Queued,
+    /// Queued for a console that is currently offline.
+    Waiting,
     Transferring,
+    /// Upload finished, checking the remote copy against the local file.
+    Verifying,
+    /// Last attempt failed, waiting out the backoff delay before the next one.
+    Retrying,
+    Paused,
     Completed,
     Failed,
     Cancelled,
 }
 
-#[derive(Debug)]
-struct TransferTask {
+/// How a transfer came to an end, short of an I/O or protocol error.
+enum TransferOutcome {
+    Completed,
+    Paused,
+    Cancelled,
+    /// The upload went through but the remote copy does not match the local file.
+    VerificationFailed(String),
+}
+
+/// User-tunable transfer behaviour, saved to `.ftp_settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransferSettings {
+    /// Check the remote size, and CRC32 where the server offers XCRC or HASH, after each upload.
+    pub verify_uploads: bool,
+    /// Upload the file again, within `max_attempts`, when verification finds a mismatch.
+    pub retry_failed_verification: bool,
+    /// Attempts per task, including the first, before a transient error fails it for good.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every retry after it.
+    pub retry_delay_secs: u64,
+    /// Upper bound for the backoff delay.
+    pub max_retry_delay_secs: u64,
+    /// Queue finished torrent downloads to the preferred console automatically.
+    pub auto_install_downloads: bool,
+    /// Upload cap in bytes per second across all transfers. None means unlimited.
+    pub upload_limit: Option<u64>,
+}
+
+impl Default for TransferSettings {
+    fn default() -> Self {
+        Self {
+            verify_uploads: true,
+            retry_failed_verification: true,
+            max_attempts: 3,
+            retry_delay_secs: 5,
+            max_retry_delay_secs: 120,
+            auto_install_downloads: false,
+            upload_limit: None,
+        }
+    }
+}
+
+impl TransferSettings {
+    /// Exponential backoff before the retry that follows the given (1-based) attempt.
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        Duration::from_secs(
+            self.retry_delay_secs
+                .saturating_mul(factor)
+                .min(self.max_retry_delay_secs),
+        )
+    }
+}
+
+/// Paces uploads to a byte rate. The budget is measured over a short window that
+/// restarts whenever the limit changes, so a new limit takes effect within a chunk.
+/// The global limiter is shared by every worker; each upload also has its own for the
+/// per-task limit.
+struct RateLimiter {
+    limit: u64,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        Self {
+            limit: 0,
+            window_start: Instant::now(),
+            window_bytes: 0,
+        }
+    }
+
+    /// The stricter of the global and per-task limits.
+    fn effective_limit(global: Option<u64>, task: Option<u64>) -> Option<u64> {
+        match (global, task) {
+            (Some(global), Some(task)) => Some(global.min(task)),
+            (limit, None) | (None, limit) => limit,
+        }
+        .filter(|limit| *limit > 0)
+    }
+
+    /// How much to write per iteration so throttled uploads stay smooth and responsive.
+    fn chunk_size(limit: Option<u64>, buffer_len: usize) -> usize {
+        match limit {
+            Some(limit) => ((limit / 8) as usize).clamp(MIN_THROTTLED_CHUNK, buffer_len),
+            None => buffer_len,
+        }
+    }
+
+    /// Books `sent` more bytes against the limit and returns how long the caller has to
+    /// wait to stay within it. The caller sleeps, so a shared limiter is not held meanwhile.
+    fn reserve(&mut self, limit: Option<u64>, sent: u64) -> Duration {
+        let Some(limit) = limit else {
+            self.limit = 0;
+            return Duration::ZERO;
+        };
+
+        if limit != self.limit
+            || self.window_start.elapsed() >= Duration::from_secs(RATE_LIMIT_WINDOW_SECS)
+        {
+            self.limit = limit;
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+
+        self.window_bytes += sent;
+        let budget = Duration::from_secs_f64(self.window_bytes as f64 / limit as f64);
+        budget.saturating_sub(self.window_start.elapsed())
+    }
+}
+
+/// One failed attempt at a task, kept so the UI can show what went wrong.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferFailure {
+    pub attempt: u32,
+    pub error: String,
+    pub transient: bool,
+    /// RFC 3339 timestamp of the failure.
+    pub failed_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferTask {
     game_file: GameFile,
     status: TransferStatus,
+    resume_offset: u64,
+    /// Registered console this task is bound to. Unbound tasks go to whichever console
+    /// the manager currently targets.
+    #[serde(default)]
+    device_id: Option<String>,
+    #[serde(default)]
+    attempts: u32,
+    #[serde(default)]
+    failures: Vec<TransferFailure>,
+    #[serde(default)]
+    bundle: Option<BundleMembership>,
+    /// Upload cap for this task in bytes per second, on top of the global one.
+    #[serde(default)]
+    upload_limit: Option<u64>,
 }
 
-pub struct FTPManager {
-    ftp_ip: Arc<Mutex<Option<String>>>,
-    transfer_queue: Arc<Mutex<VecDeque<TransferTask>>>,
-    is_transferring: Arc<AtomicBool>,
-    current_transfer: Arc<Mutex<Option<TransferProgress>>>,
+/// Bookkeeping for an upload that stopped before reaching the end of the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartialTransfer {
+    file_size: u64,
+    bytes_sent: u64,
+}
+
+/// Key into `partial_transfers`. Each console gets its own entry for a file, so
+/// uploads of the same file to several consoles resume independently.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartialKey {
+    device_id: Option<String>,
+    local_path: String,
+}
+
+impl PartialKey {
+    fn new(device_id: &Option<String>, local_path: &str) -> Self {
+        Self {
+            device_id: device_id.clone(),
+            local_path: local_path.to_string(),
+        }
+    }
+
+    fn for_task(task: &TransferTask) -> Self {
+        Self::new(&task.device_id, &task.game_file.file_path)
+    }
+}
+
+/// A `partial_transfers` entry as saved to disk, where map keys must be strings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedPartialTransfer {
+    #[serde(flatten)]
+    key: PartialKey,
+    #[serde(flatten)]
+    partial: PartialTransfer,
+}
+
+/// On-disk snapshot of the transfer queue, written to `.ftp_queue.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedTransferQueue {
+    tasks: Vec<TransferTask>,
+    #[serde(default)]
+    partial_uploads: Vec<SavedPartialTransfer>,
+}
+
+/// State of the worker uploading to one console. Each worker only takes the queued
+/// tasks bound to its device; unbound tasks share the worker keyed by None, which
+/// follows the manager's current target.
+#[derive(Clone)]
+struct TransferContext {
+    device_id: Option<String>,
     app_handle: AppHandle,
+    is_transferring: Arc<AtomicBool>,
     bytes_transferred: Arc<AtomicU64>,
     total_bytes: Arc<AtomicU64>,
+    current_transfer: Arc<Mutex<Option<TransferProgress>>>,
+    current_task: Arc<Mutex<Option<TransferTask>>>,
+    control: Arc<AtomicU8>,
+    settings: Arc<Mutex<TransferSettings>>,
+    transfer_queue: Arc<Mutex<VecDeque<TransferTask>>>,
+    partial_transfers: Arc<Mutex<HashMap<PartialKey, PartialTransfer>>>,
+    ftp_profile: Arc<Mutex<Option<FtpConnectionProfile>>>,
+    upload_limiter: Arc<Mutex<RateLimiter>>,
+    /// Per-task limit of the file in flight, 0 for none. Shared so it can change mid-upload.
+    task_upload_limit: Arc<AtomicU64>,
+}
+
+impl TransferContext {
+    /// Whether a queued task still has to run on this worker's console.
+    fn owns(&self, task: &TransferTask) -> bool {
+        task.device_id == self.device_id && task.status != TransferStatus::Failed
+    }
+}
+
+/// Result of queueing one file to one of several consoles.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceQueueResult {
+    pub device_id: String,
+    pub queued: bool,
+    pub error: Option<String>,
+}
+
+pub struct FTPManager {
+    ftp_profile: Arc<Mutex<Option<FtpConnectionProfile>>>,
+    transfer_queue: Arc<Mutex<VecDeque<TransferTask>>>,
+    /// One worker per console, created the first time something is queued to it.
+    workers: Arc<Mutex<HashMap<Option<String>, TransferContext>>>,
+    partial_transfers: Arc<Mutex<HashMap<PartialKey, PartialTransfer>>>,
+    settings: Arc<Mutex<TransferSettings>>,
+    /// Enforces the global upload limit across every console's worker.
+    upload_limiter: Arc<Mutex<RateLimiter>>,
+    /// Games already handed to the queue by auto-install, so a download is only
+    /// installed once however often its completion is reported.
+    auto_installed: Arc<Mutex<BTreeSet<u32>>>,
+    app_handle: AppHandle,
 }
 
 impl FTPManager {
     pub fn new(app_handle: AppHandle) -> Self {
         info!("FTPManager instance created");
-        Self {
-            ftp_ip: Arc::new(Mutex::new(None)),
-            transfer_queue: Arc::new(Mutex::new(VecDeque::new())),
-            is_transferring: Arc::new(AtomicBool::new(false)),
-            current_transfer: Arc::new(Mutex::new(None)),
+
+        // Read the saved profiles up front rather than on first use under the queue lock
+        let _ = ftp_connection::load_profiles();
+
+        let saved = Self::load_saved_queue();
+
+        // A console whose first task was paused stays paused until resumed
+        let mut paused_devices: Vec<Option<String>> = Vec::new();
+        let mut seen_devices: Vec<Option<String>> = Vec::new();
+        for task in &saved.tasks {
+            if seen_devices.contains(&task.device_id) {
+                continue;
+            }
+            seen_devices.push(task.device_id.clone());
+            if task.status == TransferStatus::Paused {
+                paused_devices.push(task.device_id.clone());
+            }
+        }
+
+        let manager = Self {
+            ftp_profile: Arc::new(Mutex::new(None)),
+            transfer_queue: Arc::new(Mutex::new(saved.tasks.into_iter().collect())),
+            workers: Arc::new(Mutex::new(HashMap::new())),
+            partial_transfers: Arc::new(Mutex::new(
+                saved
+                    .partial_uploads
+                    .into_iter()
+                    .map(|entry| (entry.key, entry.partial))
+                    .collect(),
+            )),
+            settings: Arc::new(Mutex::new(
+                load_config_file(FTP_SETTINGS_FILE).unwrap_or_default(),
+            )),
+            upload_limiter: Arc::new(Mutex::new(RateLimiter::new())),
+            auto_installed: Arc::new(Mutex::new(
+                load_config_file(FTP_AUTO_INSTALLED_FILE).unwrap_or_default(),
+            )),
             app_handle,
-            bytes_transferred: Arc::new(AtomicU64::new(0)),
-            total_bytes: Arc::new(AtomicU64::new(0)),
+        };
+
+        for device_id in &paused_devices {
+            manager
+                .worker(device_id)
+                .control
+                .store(CONTROL_PAUSE, Ordering::SeqCst);
+        }
+
+        info!(
+            "Starting transfer queue auto-save (interval: {}s)",
+            QUEUE_AUTO_SAVE_INTERVAL_SECS
+        );
+        manager.start_auto_save(QUEUE_AUTO_SAVE_INTERVAL_SECS);
+
+        manager
+    }
+
+    fn load_saved_queue() -> SavedTransferQueue {
+        let Some(mut saved) = load_config_file::<SavedTransferQueue>(FTP_QUEUE_FILE) else {
+            info!("No saved transfer queue found");
+            return SavedTransferQueue::default();
+        };
+
+        // Drop tasks whose local file disappeared while the app was closed
+        saved.tasks.retain(|task| {
+            let exists = Path::new(&task.game_file.file_path).exists();
+            if !exists {
+                warn!(
+                    "Skipping saved transfer, local file is gone: {}",
+                    task.game_file.file_path
+                );
+            }
+            exists
+        });
+        // Resume data is only worth keeping for files still in the queue
+        let queued: Vec<PartialKey> = saved.tasks.iter().map(PartialKey::for_task).collect();
+        saved
+            .partial_uploads
+            .retain(|entry| queued.contains(&entry.key));
+
+        // Anything that was mid-upload when the app closed goes back to the queue
+        for task in saved.tasks.iter_mut() {
+            if task.status == TransferStatus::Transferring {
+                task.status = TransferStatus::Queued;
+            }
         }
+
+        info!("Restored {} queued transfer(s)", saved.tasks.len());
+        saved
+    }
+
+    fn start_auto_save(&self, interval_secs: u64) {
+        let transfer_queue = Arc::clone(&self.transfer_queue);
+        let partial_transfers = Arc::clone(&self.partial_transfers);
+        let workers = Arc::clone(&self.workers);
+
+        thread::spawn(move || {
+            let mut last_saved = String::new();
+
+            loop {
+                thread::sleep(Duration::from_secs(interval_secs));
+
+                let mut tasks = Vec::new();
+
+                // In-flight files are popped off the queue, so save them first with their offset
+                let running: Vec<TransferContext> =
+                    workers.lock().unwrap().values().cloned().collect();
+                for worker in running {
+                    if let Some(mut task) = worker.current_task.lock().unwrap().clone() {
+                        task.resume_offset = worker.bytes_transferred.load(Ordering::SeqCst);
+                        tasks.push(task);
+                    }
+                }
+                tasks.extend(transfer_queue.lock().unwrap().iter().cloned());
+
+                let snapshot = SavedTransferQueue {
+                    tasks,
+                    partial_uploads: partial_transfers
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .map(|(key, partial)| SavedPartialTransfer {
+                            key: key.clone(),
+                            partial: partial.clone(),
+                        })
+                        .collect(),
+                };
+
+                // Only touch the disk when something actually changed
+                let serialized = serde_json::to_string(&snapshot).unwrap_or_default();
+                if serialized == last_saved {
+                    continue;
+                }
+
+                match save_config_file(FTP_QUEUE_FILE, &snapshot) {
+                    Ok(_) => {
+                        debug!(
+                            "Auto-saved transfer queue with {} task(s)",
+                            snapshot.tasks.len()
+                        );
+                        last_saved = serialized;
+                    }
+                    Err(e) => error!("Failed to save transfer queue: {}", e),
+                }
+            }
+        });
     }
 
     pub fn set_ftp_ip(&self, ip: String) -> Result<(), String> {
         info!("Setting FTP IP to: {}", ip);
 
+        // Use the saved profile for this console if there is one
+        let profile = ftp_connection::profile_for_host(&ip);
+
         // Test connection before setting
-        let test_address = format!("{}:{}", ip, DBI_FTP_PORT);
-        match TcpStream::connect_timeout(
-            &test_address
-                .parse()
-                .map_err(|e| format!("Invalid IP address: {}", e))?,
-            Duration::from_millis(FTP_TIMEOUT_MS),
-        ) {
+        match profile.test_tcp() {
             Ok(_) => {
-                let mut ftp_ip_guard = self.ftp_ip.lock().unwrap();
-                *ftp_ip_guard = Some(ip.clone());
-                info!("FTP IP set successfully: {}", ip);
+                let mut profile_guard = self.ftp_profile.lock().unwrap();
+                *profile_guard = Some(profile.clone());
+                info!("FTP IP set successfully: {}", profile.address());
                 Ok(())
             }
             Err(e) => {
-                error!("Failed to connect to FTP at {}: {}", ip, e);
-                Err(format!("Cannot connect to FTP server at {}: {}", ip, e))
+                error!("Failed to connect to FTP at {}: {}", profile.address(), e);
+                Err(e.into())
             }
         }
     }
 
     pub fn get_ftp_ip(&self) -> Option<String> {
-        self.ftp_ip.lock().unwrap().clone()
+        self.ftp_profile
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|profile| profile.host.clone())
+    }
+
+    /// Logs in with the given profile and, if that works, saves it and makes it the active target.
+    pub fn set_ftp_profile(&self, profile: FtpConnectionProfile) -> Result<(), String> {
+        info!("Testing FTP profile for {}", profile.address());
+        let profile = ftp_connection::with_saved_password(profile);
+
+        let mut ftp = profile.connect()?;
+        ftp.quit().ok();
+
+        ftp_connection::save_profile(&profile)?;
+
+        let mut profile_guard = self.ftp_profile.lock().unwrap();
+        *profile_guard = Some(profile);
+        Ok(())
+    }
+
+    pub fn get_ftp_profile(&self) -> Option<FtpConnectionProfile> {
+        self.ftp_profile.lock().unwrap().clone()
+    }
+
+    pub fn get_settings(&self) -> TransferSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Changes the global upload cap, including for the transfer in flight.
+    pub fn set_upload_limit(&self, upload_limit: Option<u64>) -> Result<(), String> {
+        let mut settings = self.get_settings();
+        settings.upload_limit = upload_limit.filter(|limit| *limit > 0);
+        self.set_settings(settings)
+    }
+
+    /// Caps queued or running tasks for a file, on top of the global limit. Without a
+    /// `device_id` the cap applies to the file on every console.
+    pub fn set_task_upload_limit(
+        &self,
+        file_path: &str,
+        device_id: Option<&str>,
+        upload_limit: Option<u64>,
+    ) -> Result<(), String> {
+        let upload_limit = upload_limit.filter(|limit| *limit > 0);
+        let mut found = false;
+
+        for worker in self.workers() {
+            let mut current = worker.current_task.lock().unwrap();
+            if let Some(task) = current
+                .as_mut()
+                .filter(|task| task_matches(task, file_path, device_id))
+            {
+                task.upload_limit = upload_limit;
+                worker
+                    .task_upload_limit
+                    .store(upload_limit.unwrap_or(0), Ordering::SeqCst);
+                found = true;
+            }
+        }
+
+        for task in self.transfer_queue.lock().unwrap().iter_mut() {
+            if task_matches(task, file_path, device_id) {
+                task.upload_limit = upload_limit;
+                found = true;
+            }
+        }
+
+        if !found {
+            return Err(format!("File not found in queue: {}", file_path));
+        }
+        info!("Upload limit for {} set to {:?}", file_path, upload_limit);
+        Ok(())
+    }
+
+    /// Applies new settings. Retry and verification changes apply from the next file,
+    /// the upload limit straight away.
+    pub fn set_settings(&self, settings: TransferSettings) -> Result<(), String> {
+        save_config_file(FTP_SETTINGS_FILE, &settings)?;
+        info!("Updated transfer settings: {:?}", settings);
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Moves the queued transfers and resume data of a console that was merged into
+    /// another registry entry or forgotten, so they no longer wait for an id that is gone.
+    /// Without `into` the transfers go to whichever console the manager targets.
+    pub fn reassign_device(&self, from: &str, into: Option<&str>) {
+        let from_id = Some(from.to_string());
+        let into_id = into.map(str::to_string);
+
+        let mut queue = self.transfer_queue.lock().unwrap();
+        let already_queued: Vec<String> = queue
+            .iter()
+            .filter(|task| task.device_id == into_id)
+            .map(|task| task.game_file.file_path.clone())
+            .collect();
+
+        let mut moved = 0;
+        queue.retain_mut(|task| {
+            if task.device_id != from_id {
+                return true;
+            }
+            // The file is queued for the new console already
+            if already_queued.contains(&task.game_file.file_path) {
+                return false;
+            }
+            task.device_id = into_id.clone();
+            moved += 1;
+            true
+        });
+        drop(queue);
+
+        let mut partial_transfers = self.partial_transfers.lock().unwrap();
+        let keys: Vec<PartialKey> = partial_transfers
+            .keys()
+            .filter(|key| key.device_id == from_id)
+            .cloned()
+            .collect();
+        for key in keys {
+            if let Some(partial) = partial_transfers.remove(&key) {
+                partial_transfers
+                    .entry(PartialKey::new(&into_id, &key.local_path))
+                    .or_insert(partial);
+            }
+        }
+        drop(partial_transfers);
+
+        // The old console's worker has nothing left to do unless a file is in flight
+        let mut workers = self.workers.lock().unwrap();
+        let idle = workers.get(&from_id).map_or(false, |worker| {
+            !worker.is_transferring.load(Ordering::SeqCst)
+        });
+        if idle {
+            workers.remove(&from_id);
+        }
+
+        info!(
+            "Moved {} queued transfer(s) from console {} to {}",
+            moved,
+            from,
+            into.unwrap_or("the current console")
+        );
+    }
+
+    /// Called when consoles come or go, or the preferred console changes, so transfers
+    /// go to the preferred device and a queue restored from disk picks up by itself.
+    pub fn handle_devices_changed(&self, registry: &DeviceRegistry) {
+        if let Some(device) = registry.preferred_target() {
+            let mut profile_guard = self.ftp_profile.lock().unwrap();
+            let already_targeted = profile_guard
+                .as_ref()
+                .map_or(false, |p| p.host == device.last_ip && p.port == device.port);
+
+            if !already_targeted {
+                let mut profile = ftp_connection::profile_for_host(&device.last_ip);
+                profile.port = device.port;
+                info!("Targeting console {} at {}", device.name, profile.address());
+                *profile_guard = Some(profile);
+            }
+        }
+
+        self.refresh_waiting_tasks();
+
+        let mut runnable: Vec<Option<String>> = Vec::new();
+        for task in self.transfer_queue.lock().unwrap().iter() {
+            if !matches!(
+                task.status,
+                TransferStatus::Waiting | TransferStatus::Failed
+            ) && !runnable.contains(&task.device_id)
+            {
+                runnable.push(task.device_id.clone());
+            }
+        }
+
+        for device_id in runnable {
+            if !self
+                .worker(&device_id)
+                .is_transferring
+                .load(Ordering::SeqCst)
+            {
+                info!("Console available, resuming pending transfers");
+                self.start_transfer_worker(&device_id);
+            }
+        }
     }
 
     pub fn scan_game_files(&self, game_path: &PathBuf) -> Result<Vec<GameFile>, String> {
@@ -135,10 +803,107 @@ impl FTPManager {
             }
         }
 
+        self.mark_unavailable(&mut game_files);
+
         info!("Found {} game files", game_files.len());
         Ok(game_files)
     }
 
+    /// Games still downloading, keyed by id, or None if the torrent state could not be read.
+    fn incomplete_downloads(&self) -> Option<HashMap<u32, Vec<PathBuf>>> {
+        match self.app_handle.try_state::<TorrentState>() {
+            Some(torrents) => torrents.incomplete_files(),
+            None => Some(HashMap::new()),
+        }
+    }
+
+    /// Why a file cannot be sent right now, if anything stops it.
+    fn unavailable_reason(
+        incomplete: &HashMap<u32, Vec<PathBuf>>,
+        game_file: &GameFile,
+    ) -> Option<String> {
+        let path = Path::new(&game_file.file_path);
+        if local_lock::is_being_written(path) {
+            return Some("file is still being written".to_string());
+        }
+
+        let files = incomplete.get(&game_file.game_id)?;
+        if files.is_empty() || files.iter().any(|relative| path.ends_with(relative)) {
+            return Some("file is still downloading".to_string());
+        }
+        None
+    }
+
+    fn mark_unavailable(&self, game_files: &mut [GameFile]) {
+        let Some(incomplete) = self.incomplete_downloads() else {
+            warn!("Torrent state busy, could not check for files still downloading");
+            return;
+        };
+
+        for game_file in game_files.iter_mut() {
+            game_file.unavailable_reason = Self::unavailable_reason(&incomplete, game_file);
+        }
+    }
+
+    /// Refuses files that are not fully on disk yet.
+    fn ensure_available(&self, game_file: &GameFile) -> Result<(), String> {
+        let incomplete = self.incomplete_downloads().ok_or_else(|| {
+            format!(
+                "Could not check whether {} is still downloading, try again",
+                game_file.file_name
+            )
+        })?;
+
+        match Self::unavailable_reason(&incomplete, game_file) {
+            Some(reason) => Err(format!(
+                "Cannot send {} yet: {}",
+                game_file.file_name, reason
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Groups the installable files of one game folder into a bundle in install order.
+    pub fn scan_game_bundle(&self, game_id: u32) -> Result<GameBundle, String> {
+        let game_dir = get_game_path().join(game_id.to_string());
+        if !game_dir.is_dir() {
+            return Err(format!("Game folder not found: {}", game_dir.display()));
+        }
+
+        let mut game_files = Vec::new();
+        self.scan_directory(&game_dir, game_id, &mut game_files)?;
+        if game_files.is_empty() {
+            return Err(format!("No installable files in {}", game_dir.display()));
+        }
+        self.mark_unavailable(&mut game_files);
+
+        let mut files: Vec<BundleFile> = game_files
+            .into_iter()
+            .map(|game_file| BundleFile {
+                kind: ContentKind::from_file_name(&game_file.file_name),
+                game_file,
+            })
+            .collect();
+        files.sort_by(|a, b| {
+            a.kind
+                .cmp(&b.kind)
+                .then_with(|| a.game_file.file_name.cmp(&b.game_file.file_name))
+        });
+
+        // Name the bundle after the base game when there is one
+        let game_title = files
+            .first()
+            .map(|file| file.game_file.game_title.clone())
+            .unwrap_or_default();
+
+        Ok(GameBundle {
+            game_id,
+            game_title,
+            total_size: files.iter().map(|file| file.game_file.file_size).sum(),
+            files,
+        })
+    }
+
     fn scan_directory(
         &self,
         dir: &Path,
@@ -182,6 +947,8 @@ impl FTPManager {
                             file_path: path.to_string_lossy().to_string(),
                             file_name,
                             file_size: metadata.len(),
+                            install_target: None,
+                            unavailable_reason: None,
                         });
                     }
                 }
@@ -194,29 +961,292 @@ impl FTPManager {
         Ok(())
     }
 
-    pub fn queue_file(&self, game_file: GameFile) -> Result<(), String> {
+    /// Queues a file for upload. With a `device_id` the task waits for that console;
+    /// without one it goes to the current target, or to the preferred registered console
+    /// when nothing is connected yet.
+    pub fn queue_file(&self, game_file: GameFile, device_id: Option<String>) -> Result<(), String> {
         info!(
             "Queueing file: {} ({})",
             game_file.file_name, game_file.game_id
         );
 
-        let mut queue = self.transfer_queue.lock().unwrap();
+        self.ensure_available(&game_file)?;
 
-        // Check if file is already queued
-        if queue
-            .iter()
-            .any(|task| task.game_file.file_path == game_file.file_path)
-        {
-            return Err(format!(
-                "File {} is already in the queue",
-                game_file.file_name
-            ));
+        let device_id = self.resolve_device_id(device_id)?;
+        self.enqueue(game_file, device_id, None)
+    }
+
+    /// Queues the same file to several registered consoles. Each console uploads it in
+    /// parallel with the others; a console that refuses the file does not stop the rest.
+    pub fn queue_file_to_devices(
+        &self,
+        game_file: GameFile,
+        device_ids: Vec<String>,
+    ) -> Result<Vec<DeviceQueueResult>, String> {
+        if device_ids.is_empty() {
+            return Err("No consoles selected".to_string());
         }
 
-        queue.push_back(TransferTask {
-            game_file: game_file.clone(),
-            status: TransferStatus::Queued,
-        });
+        info!(
+            "Queueing file {} to {} console(s)",
+            game_file.file_name,
+            device_ids.len()
+        );
+        self.ensure_available(&game_file)?;
+
+        Ok(device_ids
+            .into_iter()
+            .map(|device_id| {
+                let result = self
+                    .resolve_device_id(Some(device_id.clone()))
+                    .and_then(|device_id| self.enqueue(game_file.clone(), device_id, None));
+                if let Err(e) = &result {
+                    warn!(
+                        "Could not queue {} to {}: {}",
+                        game_file.file_name, device_id, e
+                    );
+                }
+
+                DeviceQueueResult {
+                    device_id,
+                    queued: result.is_ok(),
+                    error: result.err(),
+                }
+            })
+            .collect())
+    }
+
+    /// Queues every file of a game folder as one bundle: base game, then updates, then DLC.
+    pub fn queue_bundle(
+        &self,
+        game_id: u32,
+        device_id: Option<String>,
+        install_target: Option<InstallTarget>,
+    ) -> Result<GameBundle, String> {
+        let bundle = self.scan_game_bundle(game_id)?;
+        for file in &bundle.files {
+            self.ensure_available(&file.game_file)?;
+        }
+        info!(
+            "Queueing bundle for {} ({} file(s), {} bytes)",
+            bundle.game_title,
+            bundle.files.len(),
+            bundle.total_size
+        );
+
+        let device_id = self.resolve_device_id(device_id)?;
+
+        {
+            let queue = self.transfer_queue.lock().unwrap();
+            if let Some(queued) = bundle.files.iter().find(|file| {
+                queue.iter().any(|task| {
+                    task.game_file.file_path == file.game_file.file_path
+                        && task.device_id == device_id
+                        && task.status != TransferStatus::Failed
+                })
+            }) {
+                return Err(format!(
+                    "File {} is already in the queue",
+                    queued.game_file.file_name
+                ));
+            }
+        }
+        let bundle_id = format!(
+            "bundle-{}-{}",
+            game_id,
+            chrono::Local::now().timestamp_millis()
+        );
+
+        for file in &bundle.files {
+            let mut game_file = file.game_file.clone();
+            if install_target.is_some() {
+                game_file.install_target = install_target;
+            }
+
+            self.enqueue(
+                game_file,
+                device_id.clone(),
+                Some(BundleMembership {
+                    bundle_id: bundle_id.clone(),
+                    kind: file.kind,
+                    total_files: bundle.files.len(),
+                    total_bytes: bundle.total_size,
+                }),
+            )?;
+        }
+
+        Ok(bundle)
+    }
+
+    /// Queues a freshly downloaded game to the preferred console, if auto-install is on.
+    /// Returns None when the setting is off or the game was auto-installed before.
+    pub fn auto_install_download(&self, game_id: u32) -> Option<AutoInstallPayload> {
+        if !self.settings.lock().unwrap().auto_install_downloads {
+            return None;
+        }
+        if self.auto_installed.lock().unwrap().contains(&game_id) {
+            info!("Game {} was already auto-installed, skipping", game_id);
+            return None;
+        }
+
+        info!("Auto-installing downloaded game {}", game_id);
+
+        // Bind to the preferred console even if it is asleep, the queue waits for it
+        let device = self.device_registry().and_then(|registry| {
+            registry
+                .devices()
+                .into_iter()
+                .find(|device| device.preferred)
+        });
+
+        let result = self.queue_bundle(game_id, device.as_ref().map(|d| d.id.clone()), None);
+        match &result {
+            Ok(_) => {
+                let mut auto_installed = self.auto_installed.lock().unwrap();
+                auto_installed.insert(game_id);
+                if let Err(e) = save_config_file(FTP_AUTO_INSTALLED_FILE, &*auto_installed) {
+                    warn!("Failed to save auto-installed games: {}", e);
+                }
+            }
+            Err(e) => error!("Auto-install of game {} failed: {}", game_id, e),
+        }
+
+        Some(AutoInstallPayload {
+            game_id,
+            device_id: device.as_ref().map(|d| d.id.clone()),
+            device_name: device.map(|d| d.name),
+            bundle: result.as_ref().ok().cloned(),
+            error: result.err(),
+        })
+    }
+
+    /// Lets auto-install pick a game up again once it is downloaded anew.
+    pub fn forget_auto_install(&self, game_id: u32) {
+        let mut auto_installed = self.auto_installed.lock().unwrap();
+        if auto_installed.remove(&game_id) {
+            if let Err(e) = save_config_file(FTP_AUTO_INSTALLED_FILE, &*auto_installed) {
+                warn!("Failed to save auto-installed games: {}", e);
+            }
+        }
+    }
+
+    fn resolve_device_id(&self, device_id: Option<String>) -> Result<Option<String>, String> {
+        let registry = self.device_registry();
+        Ok(match device_id {
+            Some(device_id) => {
+                let known = registry
+                    .as_ref()
+                    .map_or(false, |registry| registry.get(&device_id).is_some());
+                if !known {
+                    return Err(format!("Unknown device: {}", device_id));
+                }
+                Some(device_id)
+            }
+            None if self.ftp_profile.lock().unwrap().is_none() => {
+                registry.as_ref().and_then(|registry| {
+                    registry
+                        .devices()
+                        .into_iter()
+                        .find(|device| device.preferred)
+                        .map(|device| device.id)
+                })
+            }
+            None => None,
+        })
+    }
+
+    fn enqueue(
+        &self,
+        mut game_file: GameFile,
+        device_id: Option<String>,
+        bundle: Option<BundleMembership>,
+    ) -> Result<(), String> {
+        let registry = self.device_registry();
+
+        // Fall back to the install destination configured for the target console
+        if game_file.install_target.is_none() {
+            game_file.install_target = registry.as_ref().and_then(|registry| {
+                let device = match device_id.as_ref() {
+                    Some(device_id) => registry.get(device_id),
+                    None => {
+                        let profile = self.ftp_profile.lock().unwrap().clone()?;
+                        registry.devices().into_iter().find(|device| {
+                            device.last_ip == profile.host && device.port == profile.port
+                        })
+                    }
+                };
+                device.and_then(|device| device.install_target)
+            });
+        }
+
+        let mut queue = self.transfer_queue.lock().unwrap();
+
+        // The same file may be queued once per console. Queueing a failed file again
+        // replaces it, keeping its failure history.
+        let mut failures = Vec::new();
+        if let Some(index) = queue.iter().position(|task| {
+            task.game_file.file_path == game_file.file_path && task.device_id == device_id
+        }) {
+            if queue[index].status != TransferStatus::Failed {
+                return Err(format!(
+                    "File {} is already in the queue",
+                    game_file.file_name
+                ));
+            }
+            if let Some(failed) = queue.remove(index) {
+                failures = failed.failures;
+            }
+        }
+
+        // Pick up where a previous attempt left off, as long as the local file is unchanged
+        let resume_offset = match self
+            .partial_transfers
+            .lock()
+            .unwrap()
+            .get(&PartialKey::new(&device_id, &game_file.file_path))
+        {
+            Some(partial) if partial.file_size == game_file.file_size => {
+                info!(
+                    "Resuming {} from byte {} of {}",
+                    game_file.file_name, partial.bytes_sent, partial.file_size
+                );
+                partial.bytes_sent
+            }
+            _ => 0,
+        };
+
+        let mut task = TransferTask {
+            game_file: game_file.clone(),
+            status: TransferStatus::Queued,
+            resume_offset,
+            device_id,
+            attempts: 0,
+            failures,
+            bundle,
+            upload_limit: None,
+        };
+
+        // Let the user know straight away when the console is asleep
+        if Self::resolve_target(&self.app_handle, &task, &self.ftp_profile).is_none() {
+            info!(
+                "No console available for {}, transfer will wait",
+                game_file.file_name
+            );
+            task.status = TransferStatus::Waiting;
+            let waiting_progress = Self::build_progress(
+                &task,
+                task.resume_offset,
+                task.game_file.file_size,
+                0.0,
+                TransferStatus::Waiting,
+            );
+            let _ = self
+                .app_handle
+                .emit("ftp-transfer-progress", &waiting_progress);
+        }
+
+        let device_id = task.device_id.clone();
+        queue.push_back(task);
 
         info!("File queued successfully. Queue length: {}", queue.len());
 
@@ -228,10 +1258,15 @@ impl FTPManager {
             },
         );
 
-        // Start transfer if not already running
-        if !self.is_transferring.load(Ordering::SeqCst) {
-            drop(queue); // Release lock before starting transfer
-            self.start_transfer_worker();
+        drop(queue); // Release lock before starting transfer
+
+        // Start this console's worker if it is not already running
+        if !self
+            .worker(&device_id)
+            .is_transferring
+            .load(Ordering::SeqCst)
+        {
+            self.start_transfer_worker(&device_id);
         }
 
         Ok(())
@@ -242,15 +1277,161 @@ impl FTPManager {
         queue.iter().map(|task| task.game_file.clone()).collect()
     }
 
-    pub fn clear_queue(&self) -> Result<(), String> {
-        if self.is_transferring.load(Ordering::SeqCst) {
-            return Err("Cannot clear queue while transfer is in progress".to_string());
+    /// Queued tasks with their status and target device.
+    pub fn get_queue_tasks(&self) -> Vec<TransferTask> {
+        self.transfer_queue
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    fn device_registry(&self) -> Option<Arc<DeviceRegistry>> {
+        self.app_handle
+            .try_state::<Arc<DeviceRegistry>>()
+            .map(|registry| registry.inner().clone())
+    }
+
+    /// Connection profile a task should be uploaded with right now, or None while its
+    /// console is offline.
+    fn resolve_target(
+        app_handle: &AppHandle,
+        task: &TransferTask,
+        ftp_profile: &Mutex<Option<FtpConnectionProfile>>,
+    ) -> Option<FtpConnectionProfile> {
+        let Some(device_id) = task.device_id.as_ref() else {
+            return ftp_profile.lock().unwrap().clone();
+        };
+
+        let registry = app_handle.try_state::<Arc<DeviceRegistry>>()?;
+        if !registry.is_online(device_id) {
+            return None;
         }
 
+        let device = registry.get(device_id)?;
+        let mut profile = ftp_connection::profile_for_host(&device.last_ip);
+        profile.port = device.port;
+        Some(profile)
+    }
+
+    /// The worker for a console, created on first use.
+    fn worker(&self, device_id: &Option<String>) -> TransferContext {
+        self.workers
+            .lock()
+            .unwrap()
+            .entry(device_id.clone())
+            .or_insert_with(|| TransferContext {
+                device_id: device_id.clone(),
+                app_handle: self.app_handle.clone(),
+                is_transferring: Arc::new(AtomicBool::new(false)),
+                bytes_transferred: Arc::new(AtomicU64::new(0)),
+                total_bytes: Arc::new(AtomicU64::new(0)),
+                current_transfer: Arc::new(Mutex::new(None)),
+                current_task: Arc::new(Mutex::new(None)),
+                control: Arc::new(AtomicU8::new(CONTROL_RUN)),
+                settings: Arc::clone(&self.settings),
+                transfer_queue: Arc::clone(&self.transfer_queue),
+                partial_transfers: Arc::clone(&self.partial_transfers),
+                ftp_profile: Arc::clone(&self.ftp_profile),
+                upload_limiter: Arc::clone(&self.upload_limiter),
+                task_upload_limit: Arc::new(AtomicU64::new(0)),
+            })
+            .clone()
+    }
+
+    fn workers(&self) -> Vec<TransferContext> {
+        self.workers.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Workers a pause, resume or cancel applies to: one console's, or all of them.
+    fn selected_workers(&self, device_id: Option<&str>) -> Vec<TransferContext> {
+        match device_id {
+            Some(device_id) => vec![self.worker(&Some(device_id.to_string()))],
+            None => self.workers(),
+        }
+    }
+
+    /// Flips queued tasks between `Queued` and `Waiting` as their consoles come and go.
+    fn refresh_waiting_tasks(&self) {
+        let mut queue = self.transfer_queue.lock().unwrap();
+        let mut changed = false;
+
+        for task in queue.iter_mut() {
+            let available =
+                Self::resolve_target(&self.app_handle, task, &self.ftp_profile).is_some();
+            let status = match task.status {
+                TransferStatus::Waiting if available => TransferStatus::Queued,
+                TransferStatus::Queued if !available => TransferStatus::Waiting,
+                _ => continue,
+            };
+
+            task.status = status.clone();
+            changed = true;
+
+            let progress = Self::build_progress(
+                task,
+                task.resume_offset,
+                task.game_file.file_size,
+                0.0,
+                status,
+            );
+            let _ = self.app_handle.emit("ftp-transfer-progress", &progress);
+        }
+
+        if changed {
+            let _ = self.app_handle.emit(
+                "ftp-queue-updated",
+                QueueUpdatePayload {
+                    queue_length: queue.len(),
+                },
+            );
+        }
+    }
+
+    /// Takes the worker's first task if its console is reachable, otherwise marks the
+    /// worker's tasks as waiting.
+    fn take_next_task(ctx: &TransferContext) -> Option<(TransferTask, FtpConnectionProfile)> {
+        let mut queue = ctx.transfer_queue.lock().unwrap();
+
+        let index = queue.iter().position(|task| ctx.owns(task))?;
+        if let Some(profile) =
+            Self::resolve_target(&ctx.app_handle, &queue[index], &ctx.ftp_profile)
+        {
+            return queue.remove(index).map(|task| (task, profile));
+        }
+
+        // Every task of a worker goes to the same console, so they all wait together
+        for task in queue.iter_mut().filter(|task| ctx.owns(task)) {
+            if task.status == TransferStatus::Queued {
+                task.status = TransferStatus::Waiting;
+                let progress = Self::build_progress(
+                    task,
+                    task.resume_offset,
+                    task.game_file.file_size,
+                    0.0,
+                    TransferStatus::Waiting,
+                );
+                let _ = ctx.app_handle.emit("ftp-transfer-progress", &progress);
+            }
+        }
+
+        None
+    }
+
+    pub fn clear_queue(&self) -> Result<(), String> {
         let mut queue = self.transfer_queue.lock().unwrap();
         queue.clear();
+        self.partial_transfers.lock().unwrap().clear();
         info!("Transfer queue cleared");
 
+        // Stop whatever is in flight as well, otherwise the workers keep uploading it
+        for worker in self.workers() {
+            if worker.current_task.lock().unwrap().is_some() {
+                worker.control.store(CONTROL_CANCEL, Ordering::SeqCst);
+            }
+        }
+
         let _ = self
             .app_handle
             .emit("ftp-queue-updated", QueueUpdatePayload { queue_length: 0 });
@@ -258,18 +1439,39 @@ impl FTPManager {
         Ok(())
     }
 
-    pub fn remove_from_queue(&self, file_path: &str) -> Result<(), String> {
-        let mut queue = self.transfer_queue.lock().unwrap();
-
-        // Check if the file being removed is currently transferring
-        if let Some(current) = self.current_transfer.lock().unwrap().as_ref() {
-            if queue.front().map(|t| &t.game_file.file_path) == Some(&file_path.to_string()) {
-                return Err("Cannot remove file that is currently transferring".to_string());
+    /// Removes a file from the queue. Without a `device_id` it is removed for every console.
+    pub fn remove_from_queue(
+        &self,
+        file_path: &str,
+        device_id: Option<&str>,
+    ) -> Result<(), String> {
+        // Removing a file that is currently transferring cancels it
+        let mut cancelled = false;
+        for worker in self.workers() {
+            let is_current = worker
+                .current_task
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map_or(false, |task| task_matches(task, file_path, device_id));
+            if is_current {
+                worker.control.store(CONTROL_CANCEL, Ordering::SeqCst);
+                info!("Transfer cancel requested");
+                cancelled = true;
             }
         }
 
+        let mut queue = self.transfer_queue.lock().unwrap();
         let original_len = queue.len();
-        queue.retain(|task| task.game_file.file_path != file_path);
+        let mut partial_transfers = self.partial_transfers.lock().unwrap();
+        queue.retain(|task| {
+            let remove = task_matches(task, file_path, device_id);
+            if remove {
+                partial_transfers.remove(&PartialKey::for_task(task));
+            }
+            !remove
+        });
+        drop(partial_transfers);
 
         if queue.len() < original_len {
             info!("Removed file from queue: {}", file_path);
@@ -280,252 +1482,1267 @@ impl FTPManager {
                 },
             );
             Ok(())
+        } else if cancelled {
+            Ok(())
         } else {
             Err(format!("File not found in queue: {}", file_path))
         }
     }
 
-    fn start_transfer_worker(&self) {
-        if self.is_transferring.load(Ordering::SeqCst) {
-            warn!("Transfer worker already running");
+    /// Queues failed files again with a fresh set of attempts. Without a `file_path` every
+    /// failed file is retried, without a `device_id` on every console.
+    pub fn retry_failed_transfers(
+        &self,
+        file_path: Option<&str>,
+        device_id: Option<&str>,
+    ) -> Result<usize, String> {
+        let mut retried = 0;
+        let mut devices: Vec<Option<String>> = Vec::new();
+        let mut queue = self.transfer_queue.lock().unwrap();
+
+        for task in queue.iter_mut() {
+            let selected = task.status == TransferStatus::Failed
+                && task_matches(
+                    task,
+                    file_path.unwrap_or(&task.game_file.file_path),
+                    device_id,
+                );
+            if !selected {
+                continue;
+            }
+
+            info!("Retrying failed transfer: {}", task.game_file.file_name);
+            task.attempts = 0;
+            task.status = TransferStatus::Queued;
+            let progress = Self::build_progress(
+                task,
+                task.resume_offset,
+                task.game_file.file_size,
+                0.0,
+                TransferStatus::Queued,
+            );
+            let _ = self.app_handle.emit("ftp-transfer-progress", &progress);
+
+            retried += 1;
+            if !devices.contains(&task.device_id) {
+                devices.push(task.device_id.clone());
+            }
+        }
+
+        if retried == 0 {
+            return Err(match file_path {
+                Some(file_path) => format!("No failed transfer for: {}", file_path),
+                None => "No failed transfers to retry".to_string(),
+            });
+        }
+
+        drop(queue);
+
+        // Files whose console is asleep go back to waiting for it
+        self.refresh_waiting_tasks();
+
+        for device_id in devices {
+            if !self
+                .worker(&device_id)
+                .is_transferring
+                .load(Ordering::SeqCst)
+            {
+                self.start_transfer_worker(&device_id);
+            }
+        }
+
+        Ok(retried)
+    }
+
+    /// Drops every failed file from the queue, along with its resume data.
+    pub fn clear_failed_transfers(&self) -> usize {
+        let mut queue = self.transfer_queue.lock().unwrap();
+        let original_len = queue.len();
+        let mut partial_transfers = self.partial_transfers.lock().unwrap();
+        queue.retain(|task| {
+            let failed = task.status == TransferStatus::Failed;
+            if failed {
+                partial_transfers.remove(&PartialKey::for_task(task));
+            }
+            !failed
+        });
+        drop(partial_transfers);
+
+        let cleared = original_len - queue.len();
+        if cleared > 0 {
+            info!("Cleared {} failed transfer(s)", cleared);
+            let _ = self.app_handle.emit(
+                "ftp-queue-updated",
+                QueueUpdatePayload {
+                    queue_length: queue.len(),
+                },
+            );
+        }
+        cleared
+    }
+
+    /// Pauses one console's worker, or every running one without a `device_id`.
+    pub fn pause_transfer(&self, device_id: Option<&str>) -> Result<(), String> {
+        let mut paused = false;
+        for worker in self.selected_workers(device_id) {
+            if worker.is_transferring.load(Ordering::SeqCst) {
+                worker.control.store(CONTROL_PAUSE, Ordering::SeqCst);
+                paused = true;
+            }
+        }
+
+        if !paused {
+            return Err("No transfer in progress".to_string());
+        }
+
+        info!("Transfer pause requested");
+        Ok(())
+    }
+
+    pub fn resume_transfer(&self, device_id: Option<&str>) -> Result<(), String> {
+        let paused: Vec<TransferContext> = self
+            .selected_workers(device_id)
+            .into_iter()
+            .filter(|worker| worker.control.load(Ordering::SeqCst) == CONTROL_PAUSE)
+            .collect();
+
+        if paused.is_empty() {
+            return Err("Transfers are not paused".to_string());
+        }
+
+        if paused
+            .iter()
+            .any(|worker| worker.is_transferring.load(Ordering::SeqCst))
+        {
+            return Err("Transfer is still pausing, try again shortly".to_string());
+        }
+
+        info!("Resuming transfers");
+        for worker in paused {
+            worker.control.store(CONTROL_RUN, Ordering::SeqCst);
+
+            let has_tasks = self
+                .transfer_queue
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|task| worker.owns(task));
+            if has_tasks {
+                self.start_transfer_worker(&worker.device_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancels the file in flight on one console, or on all of them without a `device_id`.
+    pub fn cancel_transfer(&self, device_id: Option<&str>) -> Result<(), String> {
+        let mut cancelled = false;
+
+        for worker in self.selected_workers(device_id) {
+            if worker.current_task.lock().unwrap().is_some() {
+                worker.control.store(CONTROL_CANCEL, Ordering::SeqCst);
+                info!("Transfer cancel requested");
+                cancelled = true;
+                continue;
+            }
+
+            // Nothing in flight, but a paused file waiting at the head of the worker's
+            // queue can be dropped
+            let mut queue = self.transfer_queue.lock().unwrap();
+            let Some(index) = queue.iter().position(|task| worker.owns(task)) else {
+                continue;
+            };
+            if !matches!(
+                queue[index].status,
+                TransferStatus::Paused | TransferStatus::Retrying
+            ) {
+                continue;
+            }
+
+            if let Some(mut task) = queue.remove(index) {
+                info!("Cancelling paused transfer: {}", task.game_file.file_name);
+                task.status = TransferStatus::Cancelled;
+                self.partial_transfers
+                    .lock()
+                    .unwrap()
+                    .remove(&PartialKey::for_task(&task));
+
+                let progress = Self::build_progress(
+                    &task,
+                    task.resume_offset,
+                    task.game_file.file_size,
+                    0.0,
+                    TransferStatus::Cancelled,
+                );
+                let _ = self.app_handle.emit("ftp-transfer-progress", &progress);
+            }
+
+            let _ = self.app_handle.emit(
+                "ftp-queue-updated",
+                QueueUpdatePayload {
+                    queue_length: queue.len(),
+                },
+            );
+            cancelled = true;
+        }
+
+        if !cancelled {
+            return Err("No transfer in progress".to_string());
+        }
+        Ok(())
+    }
+
+    fn start_transfer_worker(&self, device_id: &Option<String>) {
+        let ctx = self.worker(device_id);
+        let label = device_id
+            .as_deref()
+            .unwrap_or("current console")
+            .to_string();
+
+        if ctx.is_transferring.load(Ordering::SeqCst) {
+            warn!("Transfer worker for {} already running", label);
             return;
         }
 
-        info!("Starting transfer worker");
-        self.is_transferring.store(true, Ordering::SeqCst);
+        if ctx.control.load(Ordering::SeqCst) == CONTROL_PAUSE {
+            info!("Transfers to {} are paused, not starting worker", label);
+            return;
+        }
 
-        let is_transferring = Arc::clone(&self.is_transferring);
-        let transfer_queue = Arc::clone(&self.transfer_queue);
-        let current_transfer = Arc::clone(&self.current_transfer);
-        let ftp_ip = Arc::clone(&self.ftp_ip);
-        let app_handle = self.app_handle.clone();
-        let bytes_transferred = Arc::clone(&self.bytes_transferred);
-        let total_bytes = Arc::clone(&self.total_bytes);
+        info!("Starting transfer worker for {}", label);
+        ctx.is_transferring.store(true, Ordering::SeqCst);
 
         thread::spawn(move || {
-            info!("Transfer worker thread started");
+            info!("Transfer worker thread for {} started", label);
+            let app_handle = ctx.app_handle.clone();
+
+            while ctx.is_transferring.load(Ordering::SeqCst) {
+                // A pause requested between two files stops the worker before the next one
+                if ctx.control.load(Ordering::SeqCst) == CONTROL_PAUSE {
+                    info!("Transfers to {} paused, stopping worker", label);
+                    ctx.is_transferring.store(false, Ordering::SeqCst);
+                    break;
+                }
 
-            while is_transferring.load(Ordering::SeqCst) {
-                // Get next task from queue
-                let task = {
-                    let mut queue = transfer_queue.lock().unwrap();
-                    queue.pop_front()
-                };
+                // Get the next task if this worker's console is online
+                let next = Self::take_next_task(&ctx);
 
-                match task {
-                    Some(mut task) => {
+                match next {
+                    Some((mut task, profile)) => {
                         info!("Processing transfer for: {}", task.game_file.file_name);
-
-                        // Get FTP IP
-                        let ftp_ip_str = {
-                            let ip_guard = ftp_ip.lock().unwrap();
-                            match ip_guard.as_ref() {
-                                Some(ip) => ip.clone(),
-                                None => {
-                                    error!("No FTP IP set");
-                                    task.status = TransferStatus::Failed;
-                                    let _ = app_handle.emit(
-                                        "ftp-transfer-error",
-                                        TransferErrorPayload {
-                                            game_id: task.game_file.game_id,
-                                            file_name: task.game_file.file_name.clone(),
-                                            error: "No FTP IP configured".to_string(),
-                                        },
-                                    );
-                                    continue;
-                                }
-                            }
-                        };
+                        let resume_key = PartialKey::for_task(&task);
+
+                        // A cancel aimed at the previous file must not hit this one
+                        let _ = ctx.control.compare_exchange(
+                            CONTROL_CANCEL,
+                            CONTROL_RUN,
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                        );
 
                         // Initialize progress
-                        bytes_transferred.store(0, Ordering::SeqCst);
-                        total_bytes.store(task.game_file.file_size, Ordering::SeqCst);
-
-                        let progress = TransferProgress {
-                            game_id: task.game_file.game_id,
-                            file_name: task.game_file.file_name.clone(),
-                            bytes_transferred: 0,
-                            total_bytes: task.game_file.file_size,
-                            progress_percent: 0.0,
-                            transfer_speed: 0.0,
-                            eta_seconds: 0,
-                            status: TransferStatus::Transferring,
-                        };
+                        task.status = TransferStatus::Transferring;
+                        task.attempts += 1;
+                        ctx.task_upload_limit
+                            .store(task.upload_limit.unwrap_or(0), Ordering::SeqCst);
+                        ctx.bytes_transferred
+                            .store(task.resume_offset, Ordering::SeqCst);
+                        ctx.total_bytes
+                            .store(task.game_file.file_size, Ordering::SeqCst);
+
+                        let progress = Self::build_progress(
+                            &task,
+                            task.resume_offset,
+                            task.game_file.file_size,
+                            0.0,
+                            TransferStatus::Transferring,
+                        );
 
                         {
-                            let mut current = current_transfer.lock().unwrap();
+                            let mut current = ctx.current_transfer.lock().unwrap();
                             *current = Some(progress.clone());
                         }
+                        *ctx.current_task.lock().unwrap() = Some(task.clone());
 
-                        let _ = app_handle.emit("ftp-transfer-progress", &progress);
+                        Self::emit_progress(&ctx, &task, &progress);
 
                         // Perform transfer
-                        let result = Self::transfer_file(
-                            &task.game_file,
-                            &ftp_ip_str,
-                            &app_handle,
-                            &bytes_transferred,
-                            &total_bytes,
-                        );
+                        let result = Self::transfer_file(&task, &profile, &ctx);
+
+                        // A cancel or cleared queue wins over an error that came with it,
+                        // the task must not come back as failed
+                        let result = match result {
+                            Err(e) if ctx.control.load(Ordering::SeqCst) == CONTROL_CANCEL => {
+                                info!(
+                                    "Ignoring error of cancelled transfer {}: {}",
+                                    task.game_file.file_name, e
+                                );
+                                Ok(TransferOutcome::Cancelled)
+                            }
+                            result => result,
+                        };
+
+                        let bytes_sent = ctx.bytes_transferred.load(Ordering::SeqCst);
+                        let settings = ctx.settings.lock().unwrap().clone();
+                        let mut retry_delay = None;
 
                         match result {
-                            Ok(_) => {
+                            Ok(TransferOutcome::Completed) => {
                                 info!(
                                     "Transfer completed successfully: {}",
                                     task.game_file.file_name
                                 );
                                 task.status = TransferStatus::Completed;
+                                ctx.partial_transfers.lock().unwrap().remove(&resume_key);
+
+                                let final_progress = Self::build_progress(
+                                    &task,
+                                    task.game_file.file_size,
+                                    task.game_file.file_size,
+                                    0.0,
+                                    TransferStatus::Completed,
+                                );
 
-                                let final_progress = TransferProgress {
-                                    game_id: task.game_file.game_id,
-                                    file_name: task.game_file.file_name.clone(),
-                                    bytes_transferred: task.game_file.file_size,
-                                    total_bytes: task.game_file.file_size,
-                                    progress_percent: 100.0,
-                                    transfer_speed: 0.0,
-                                    eta_seconds: 0,
-                                    status: TransferStatus::Completed,
-                                };
-
-                                let _ = app_handle.emit("ftp-transfer-progress", &final_progress);
+                                Self::emit_progress(&ctx, &task, &final_progress);
                                 let _ = app_handle.emit(
                                     "ftp-transfer-complete",
                                     TransferCompletePayload {
                                         game_id: task.game_file.game_id,
                                         file_name: task.game_file.file_name.clone(),
+                                        device_id: task.device_id.clone(),
+                                    },
+                                );
+                            }
+                            Ok(TransferOutcome::Paused) => {
+                                info!(
+                                    "Transfer paused: {} at byte {}",
+                                    task.game_file.file_name, bytes_sent
+                                );
+                                task.status = TransferStatus::Paused;
+                                task.resume_offset = bytes_sent;
+                                // Pausing does not use up an attempt
+                                task.attempts = task.attempts.saturating_sub(1);
+                                ctx.partial_transfers.lock().unwrap().insert(
+                                    resume_key,
+                                    PartialTransfer {
+                                        file_size: task.game_file.file_size,
+                                        bytes_sent,
+                                    },
+                                );
+
+                                let paused_progress = Self::build_progress(
+                                    &task,
+                                    bytes_sent,
+                                    task.game_file.file_size,
+                                    0.0,
+                                    TransferStatus::Paused,
+                                );
+                                Self::emit_progress(&ctx, &task, &paused_progress);
+
+                                // Keep the paused task at the head of the queue so resume picks it up
+                                ctx.transfer_queue.lock().unwrap().push_front(task);
+                            }
+                            Ok(TransferOutcome::VerificationFailed(reason)) => {
+                                let error = format!("Verification failed: {}", reason);
+                                error!("{}: {}", task.game_file.file_name, error);
+                                ctx.partial_transfers.lock().unwrap().remove(&resume_key);
+
+                                // A bad copy is sent again from scratch, without waiting
+                                let retrying = settings.retry_failed_verification
+                                    && task.attempts < settings.max_attempts;
+                                Self::record_failure(&mut task, error.clone(), true);
+                                task.resume_offset = 0;
+                                task.status = if retrying {
+                                    TransferStatus::Queued
+                                } else {
+                                    TransferStatus::Failed
+                                };
+
+                                // Only report a failure once no retry follows
+                                let verify_progress = Self::build_progress(
+                                    &task,
+                                    if retrying { 0 } else { bytes_sent },
+                                    task.game_file.file_size,
+                                    0.0,
+                                    task.status.clone(),
+                                );
+                                Self::emit_progress(&ctx, &task, &verify_progress);
+                                let _ = app_handle.emit(
+                                    "ftp-transfer-error",
+                                    TransferErrorPayload {
+                                        game_id: task.game_file.game_id,
+                                        file_name: task.game_file.file_name.clone(),
+                                        device_id: task.device_id.clone(),
+                                        error,
+                                        attempt: task.attempts,
+                                        retrying,
                                     },
                                 );
+
+                                if retrying {
+                                    info!("Retrying upload of {}", task.game_file.file_name);
+                                    ctx.transfer_queue.lock().unwrap().push_front(task);
+                                } else {
+                                    // Failed files stay listed until the user retries or clears them
+                                    ctx.transfer_queue.lock().unwrap().push_back(task);
+                                }
+                            }
+                            Ok(TransferOutcome::Cancelled) => {
+                                info!("Transfer cancelled: {}", task.game_file.file_name);
+                                task.status = TransferStatus::Cancelled;
+                                ctx.partial_transfers.lock().unwrap().remove(&resume_key);
+
+                                let cancelled_progress = Self::build_progress(
+                                    &task,
+                                    bytes_sent,
+                                    task.game_file.file_size,
+                                    0.0,
+                                    TransferStatus::Cancelled,
+                                );
+                                Self::emit_progress(&ctx, &task, &cancelled_progress);
+
+                                // Cancelling only applies to the file in flight, carry on with the rest
+                                ctx.control.store(CONTROL_RUN, Ordering::SeqCst);
                             }
                             Err(e) => {
-                                error!("Transfer failed: {} - {}", task.game_file.file_name, e);
-                                task.status = TransferStatus::Failed;
+                                error!(
+                                    "Transfer failed: {} (attempt {}) - {}",
+                                    task.game_file.file_name, task.attempts, e
+                                );
+
+                                // Remember how far we got so a retry or re-queue can resume
+                                if bytes_sent > 0 && bytes_sent < task.game_file.file_size {
+                                    info!(
+                                        "Recording partial transfer for {} at byte {}",
+                                        task.game_file.file_name, bytes_sent
+                                    );
+                                    task.resume_offset = bytes_sent;
+                                    ctx.partial_transfers.lock().unwrap().insert(
+                                        resume_key,
+                                        PartialTransfer {
+                                            file_size: task.game_file.file_size,
+                                            bytes_sent,
+                                        },
+                                    );
+                                }
+
+                                // Network trouble usually clears up, a bad local file,
+                                // login or install folder does not
+                                let transient = e.is_transient();
+                                let retrying = transient
+                                    && task.attempts < settings.max_attempts
+                                    && ctx.control.load(Ordering::SeqCst) == CONTROL_RUN;
+                                let e = e.to_string();
+                                Self::record_failure(&mut task, e.clone(), transient);
 
                                 let _ = app_handle.emit(
                                     "ftp-transfer-error",
                                     TransferErrorPayload {
                                         game_id: task.game_file.game_id,
                                         file_name: task.game_file.file_name.clone(),
+                                        device_id: task.device_id.clone(),
                                         error: e,
+                                        attempt: task.attempts,
+                                        retrying,
                                     },
                                 );
+
+                                if retrying {
+                                    let delay = settings.retry_delay(task.attempts);
+                                    info!(
+                                        "Retrying {} in {}s (attempt {} of {})",
+                                        task.game_file.file_name,
+                                        delay.as_secs(),
+                                        task.attempts + 1,
+                                        settings.max_attempts
+                                    );
+                                    task.status = TransferStatus::Retrying;
+
+                                    let retry_progress = Self::build_progress(
+                                        &task,
+                                        task.resume_offset,
+                                        task.game_file.file_size,
+                                        0.0,
+                                        TransferStatus::Retrying,
+                                    );
+                                    Self::emit_progress(&ctx, &task, &retry_progress);
+
+                                    ctx.transfer_queue.lock().unwrap().push_front(task);
+                                    retry_delay = Some(delay);
+                                } else {
+                                    task.status = TransferStatus::Failed;
+                                    let failed_progress = Self::build_progress(
+                                        &task,
+                                        bytes_sent,
+                                        task.game_file.file_size,
+                                        0.0,
+                                        TransferStatus::Failed,
+                                    );
+                                    Self::emit_progress(&ctx, &task, &failed_progress);
+                                    ctx.transfer_queue.lock().unwrap().push_back(task);
+                                }
                             }
                         }
 
                         // Clear current transfer
                         {
-                            let mut current = current_transfer.lock().unwrap();
+                            let mut current = ctx.current_transfer.lock().unwrap();
                             *current = None;
                         }
+                        *ctx.current_task.lock().unwrap() = None;
 
                         // Emit queue update
-                        let queue_len = transfer_queue.lock().unwrap().len();
+                        let queue_len = ctx.transfer_queue.lock().unwrap().len();
                         let _ = app_handle.emit(
                             "ftp-queue-updated",
                             QueueUpdatePayload {
                                 queue_length: queue_len,
                             },
                         );
+
+                        // Back off before the retry, waking early for pause, cancel or a cleared queue
+                        if let Some(delay) = retry_delay {
+                            let deadline = Instant::now() + delay;
+                            while Instant::now() < deadline
+                                && ctx.control.load(Ordering::SeqCst) == CONTROL_RUN
+                                && ctx
+                                    .transfer_queue
+                                    .lock()
+                                    .unwrap()
+                                    .iter()
+                                    .any(|task| ctx.owns(task))
+                            {
+                                thread::sleep(Duration::from_millis(PROGRESS_EMIT_INTERVAL_MS));
+                            }
+                        }
                     }
                     None => {
-                        // Queue is empty, stop worker
-                        info!("Transfer queue empty, stopping worker");
-                        is_transferring.store(false, Ordering::SeqCst);
+                        // Nothing left for this console, or its console is offline
+                        let queue = ctx.transfer_queue.lock().unwrap();
+                        let waiting = queue.iter().filter(|task| ctx.owns(task)).count();
+                        if waiting > 0 {
+                            info!(
+                                "{} transfer(s) waiting for {}, stopping worker",
+                                waiting, label
+                            );
+                            let _ = app_handle.emit(
+                                "ftp-queue-updated",
+                                QueueUpdatePayload {
+                                    queue_length: queue.len(),
+                                },
+                            );
+                        } else {
+                            info!("No transfers left for {}, stopping worker", label);
+                        }
+                        ctx.is_transferring.store(false, Ordering::SeqCst);
                         break;
                     }
                 }
             }
 
-            info!("Transfer worker thread stopped");
+            info!("Transfer worker thread for {} stopped", label);
         });
     }
 
     fn transfer_file(
-        game_file: &GameFile,
-        ftp_ip: &str,
-        app_handle: &AppHandle,
-        bytes_transferred: &Arc<AtomicU64>,
-        total_bytes: &Arc<AtomicU64>,
-    ) -> Result<(), String> {
+        task: &TransferTask,
+        profile: &FtpConnectionProfile,
+        ctx: &TransferContext,
+    ) -> Result<TransferOutcome, TransferError> {
         use std::fs::File;
-        use std::io::Cursor;
-        use std::io::Read;
-        use std::time::Instant;
-        use suppaftp::native_tls::TlsConnector;
-        use suppaftp::types::FileType;
-        use suppaftp::FtpStream;
+
+        let game_file = &task.game_file;
+        let resume_offset = task.resume_offset;
 
         info!(
-            "Starting FTP transfer: {} -> {}",
-            game_file.file_name, ftp_ip
+            "Starting FTP transfer: {} -> {} (attempt {})",
+            game_file.file_name,
+            profile.address(),
+            task.attempts
         );
 
-        // Connect to FTP server
-        let mut ftp = FtpStream::connect(format!("{}:5000", ftp_ip))
-            .map_err(|e| format!("Failed to connect to FTP server: {}", e))?;
+        // Keep the file from being overwritten or deleted while it is sent
+        let _lock = LocalFileLock::read(Path::new(&game_file.file_path))
+            .map_err(TransferError::Transient)?;
 
-        // Login anonymously; replace with username/password if needed
-        ftp.login("anonymous", "anonymous")
-            .map_err(|e| format!("FTP login failed: {}", e))?;
+        // Connect and log in with the console's connection profile
+        let mut ftp = profile.connect()?;
 
-        ftp.transfer_type(FileType::Binary)
-            .map_err(|e| format!("Failed to set binary mode: {}", e))?;
+        if let Some(target) = game_file.install_target {
+            Self::enter_install_folder(&mut ftp, target)?;
+        }
 
         // Open local file
         let mut file = File::open(&game_file.file_path)
-            .map_err(|e| format!("Failed to open local file: {}", e))?;
+            .map_err(|e| TransferError::Fatal(format!("Failed to open local file: {}", e)))?;
+
+        let file_size = file
+            .metadata()
+            .map(|m| m.len())
+            .unwrap_or(game_file.file_size);
+        ctx.total_bytes.store(file_size, Ordering::SeqCst);
+        let verify = ctx.settings.lock().unwrap().verify_uploads;
+
+        // Work out where to start from. The remote SIZE is the source of truth for
+        // how much actually landed on the console before the connection dropped.
+        let mut offset = 0u64;
+        if resume_offset > 0 {
+            match ftp.size(&game_file.file_name) {
+                Ok(remote_size) if remote_size as u64 == file_size => {
+                    // A file of the right size may still be a bad earlier attempt or a
+                    // different file with the same name. Without verification there is no
+                    // telling, so it is sent again.
+                    let mismatch = if verify {
+                        let verifying = Self::build_progress(
+                            task,
+                            file_size,
+                            file_size,
+                            0.0,
+                            TransferStatus::Verifying,
+                        );
+                        Self::emit_progress(ctx, task, &verifying);
+                        Self::verify_upload(&mut ftp, game_file, file_size, None)
+                    } else {
+                        Some("verification is turned off".to_string())
+                    };
+
+                    match mismatch {
+                        None => {
+                            info!(
+                                "Remote file {} is already complete, skipping upload",
+                                game_file.file_name
+                            );
+                            ctx.bytes_transferred.store(file_size, Ordering::SeqCst);
+                            ftp.quit().ok();
+                            return Ok(TransferOutcome::Completed);
+                        }
+                        Some(reason) => warn!(
+                            "Remote file {} has the full size but cannot be trusted ({}), uploading again",
+                            game_file.file_name, reason
+                        ),
+                    }
+                }
+                Ok(remote_size) if remote_size > 0 && (remote_size as u64) < file_size => {
+                    match ftp.resume_transfer(remote_size) {
+                        Ok(_) => offset = remote_size as u64,
+                        Err(e) => warn!(
+                            "Server rejected REST for {}, restarting from zero: {}",
+                            game_file.file_name, e
+                        ),
+                    }
+                }
+                Ok(remote_size) => warn!(
+                    "Remote size {} does not match a partial upload of {}, restarting from zero",
+                    remote_size, game_file.file_name
+                ),
+                Err(e) => warn!(
+                    "Could not query remote size of {}, restarting from zero: {}",
+                    game_file.file_name, e
+                ),
+            }
+        }
+
+        if offset > 0 {
+            file.seek(SeekFrom::Start(offset))
+                .map_err(|e| TransferError::Fatal(format!("Failed to seek local file: {}", e)))?;
+            info!(
+                "Resuming upload of {} at byte {} of {}",
+                game_file.file_name, offset, file_size
+            );
+        }
+        ctx.bytes_transferred.store(offset, Ordering::SeqCst);
 
-        let mut buffer = vec![0u8; (TRANSFER_THROUGHPUT_BUFFER * 1024).try_into().unwrap()];
-        let mut _total_sent = 0u64;
+        // Hash fresh uploads as they stream so verification does not re-read the file
+        let mut hasher = (verify && offset == 0).then(crc32fast::Hasher::new);
+
+        let mut buffer = vec![0u8; (TRANSFER_THROUGHPUT_BUFFER * 1024) as usize];
+        let mut task_limiter = RateLimiter::new();
+        let mut total_sent = offset;
         let start_time = Instant::now();
         let mut last_update = Instant::now();
+        let mut last_update_bytes = offset;
+        let mut transfer_speed = 0.0f64;
+
+        // Open the data channel and stream the file through the buffer
+        let mut stream = ftp.put_with_stream(&game_file.file_name).map_err(|e| {
+            TransferError::Transient(format!("Failed to open FTP data stream: {}", e))
+        })?;
+
+        loop {
+            // Pause and cancel are checked once per chunk so they take effect promptly
+            match ctx.control.load(Ordering::SeqCst) {
+                CONTROL_PAUSE => {
+                    info!(
+                        "Pausing upload of {} at byte {}",
+                        game_file.file_name, total_sent
+                    );
+                    // Closing the data channel leaves a partial file that REST can continue
+                    let _ = ftp.finalize_put_stream(stream);
+                    ftp.quit().ok();
+                    return Ok(TransferOutcome::Paused);
+                }
+                CONTROL_CANCEL => {
+                    info!(
+                        "Cancelling upload of {} at byte {}",
+                        game_file.file_name, total_sent
+                    );
+                    let _ = ftp.finalize_put_stream(stream);
+                    // Don't leave a truncated file behind on the console
+                    if let Err(e) = ftp.rm(&game_file.file_name) {
+                        warn!(
+                            "Failed to remove partial remote file {}: {}",
+                            game_file.file_name, e
+                        );
+                    }
+                    ftp.quit().ok();
+                    return Ok(TransferOutcome::Cancelled);
+                }
+                _ => {}
+            }
+
+            // Limits are re-read every chunk so they can be changed mid-upload
+            let global_limit = ctx.settings.lock().unwrap().upload_limit;
+            let task_limit = ctx.task_upload_limit.load(Ordering::SeqCst);
+            let task_limit = (task_limit > 0).then_some(task_limit);
+            let chunk = RateLimiter::chunk_size(
+                RateLimiter::effective_limit(global_limit, task_limit),
+                buffer.len(),
+            );
+
+            let read = file
+                .read(&mut buffer[..chunk])
+                .map_err(|e| TransferError::Fatal(format!("Failed to read local file: {}", e)))?;
+
+            if read == 0 {
+                break;
+            }
+
+            stream
+                .write_all(&buffer[..read])
+                .map_err(|e| TransferError::Transient(format!("FTP upload failed: {}", e)))?;
+
+            // Wait for whichever of the shared and per-task budgets is further behind
+            let global_wait = ctx
+                .upload_limiter
+                .lock()
+                .unwrap()
+                .reserve(global_limit, read as u64);
+            let wait = global_wait.max(task_limiter.reserve(task_limit, read as u64));
+            if !wait.is_zero() {
+                thread::sleep(wait);
+            }
+
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&buffer[..read]);
+            }
+
+            total_sent += read as u64;
+            ctx.bytes_transferred.store(total_sent, Ordering::SeqCst);
+
+            let since_update = last_update.elapsed();
+            if since_update >= Duration::from_millis(PROGRESS_EMIT_INTERVAL_MS) {
+                let instant_speed =
+                    (total_sent - last_update_bytes) as f64 / since_update.as_secs_f64();
+
+                // Smooth the speed so the ETA does not jump around on every tick
+                transfer_speed = if transfer_speed == 0.0 {
+                    instant_speed
+                } else {
+                    transfer_speed * 0.7 + instant_speed * 0.3
+                };
+
+                let progress = Self::build_progress(
+                    task,
+                    total_sent,
+                    file_size,
+                    transfer_speed,
+                    TransferStatus::Transferring,
+                );
+
+                {
+                    let mut current = ctx.current_transfer.lock().unwrap();
+                    *current = Some(progress.clone());
+                }
+
+                Self::emit_progress(ctx, task, &progress);
+
+                last_update = Instant::now();
+                last_update_bytes = total_sent;
+            }
+        }
+
+        // Close the data channel and wait for the server to acknowledge the upload
+        ftp.finalize_put_stream(stream).map_err(|e| {
+            TransferError::Transient(format!("Failed to finalize FTP upload: {}", e))
+        })?;
+
+        let mismatch = if verify {
+            let verifying =
+                Self::build_progress(task, total_sent, file_size, 0.0, TransferStatus::Verifying);
+            Self::emit_progress(ctx, task, &verifying);
+
+            Self::verify_upload(
+                &mut ftp,
+                game_file,
+                file_size,
+                hasher.map(|hasher| hasher.finalize()),
+            )
+        } else {
+            None
+        };
+
+        ftp.quit().ok(); // ignore quit errors
+
+        if let Some(reason) = mismatch {
+            return Ok(TransferOutcome::VerificationFailed(reason));
+        }
+
+        let elapsed = start_time.elapsed().as_secs_f64();
+        info!(
+            "FTP transfer completed: {} ({} bytes in {:.2}s, {:.2} MB/s)",
+            game_file.file_name,
+            total_sent - offset,
+            elapsed,
+            (total_sent - offset) as f64 / elapsed.max(f64::EPSILON) / 1_000_000.0
+        );
+
+        Ok(TransferOutcome::Completed)
+    }
+
+    /// Compares the uploaded file with the local one. Returns the reason for a mismatch,
+    /// or None if the remote copy checks out (or the server cannot tell us).
+    fn verify_upload(
+        ftp: &mut FtpConnection,
+        game_file: &GameFile,
+        file_size: u64,
+        streamed_crc: Option<u32>,
+    ) -> Option<String> {
+        match ftp.size(&game_file.file_name) {
+            Ok(remote_size) if remote_size as u64 != file_size => {
+                return Some(format!(
+                    "remote size is {} bytes, expected {}",
+                    remote_size, file_size
+                ));
+            }
+            Ok(_) => debug!("Remote size of {} matches", game_file.file_name),
+            Err(e) => {
+                warn!(
+                    "Could not query remote size of {}, skipping verification: {}",
+                    game_file.file_name, e
+                );
+                return None;
+            }
+        }
+
+        let Some(remote_crc) = Self::remote_crc32(ftp, &game_file.file_name) else {
+            debug!(
+                "Server offers no checksum for {}, size check only",
+                game_file.file_name
+            );
+            return None;
+        };
+
+        // Resumed uploads were not hashed in full while streaming
+        let local_crc = match streamed_crc {
+            Some(crc) => crc,
+            None => match Self::local_crc32(&game_file.file_path) {
+                Ok(crc) => crc,
+                Err(e) => {
+                    warn!("{}, skipping checksum comparison", e);
+                    return None;
+                }
+            },
+        };
+
+        if remote_crc != local_crc {
+            return Some(format!(
+                "CRC32 mismatch, remote {:08x} but local {:08x}",
+                remote_crc, local_crc
+            ));
+        }
+
+        info!("Verified {} (CRC32 {:08x})", game_file.file_name, local_crc);
+        None
+    }
+
+    /// Asks the server for the file's CRC32, first with XCRC and then with HASH.
+    fn remote_crc32(ftp: &mut FtpConnection, file_name: &str) -> Option<u32> {
+        let parse_hex = |token: &str| {
+            let token = token.trim_start_matches("0x").trim_start_matches("0X");
+            u32::from_str_radix(token, 16).ok()
+        };
+
+        // "250 1A2B3C4D"
+        if let Ok(response) = ftp.custom_command(
+            format!("XCRC {}", file_name),
+            &[
+                Status::CommandOk,
+                Status::File,
+                Status::RequestedFileActionOk,
+            ],
+        ) {
+            let line = response.as_string().unwrap_or_default();
+            if let Some(crc) = line.split_whitespace().nth(1).and_then(parse_hex) {
+                return Some(crc);
+            }
+        }
+
+        // "213 CRC32 0-1234 1a2b3c4d file.nsp"
+        if ftp
+            .custom_command("OPTS HASH CRC32", &[Status::CommandOk])
+            .is_ok()
+        {
+            if let Ok(response) = ftp.custom_command(format!("HASH {}", file_name), &[Status::File])
+            {
+                let line = response.as_string().unwrap_or_default();
+                let tokens: Vec<&str> = line.split_whitespace().collect();
+                if tokens.len() > 3 && tokens[1].eq_ignore_ascii_case("CRC32") {
+                    return parse_hex(tokens[3]);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn local_crc32(file_path: &str) -> Result<u32, String> {
+        let mut file = fs::File::open(file_path)
+            .map_err(|e| format!("Failed to open {} for hashing: {}", file_path, e))?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buffer = vec![0u8; (TRANSFER_THROUGHPUT_BUFFER * 1024) as usize];
+
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .map_err(|e| format!("Failed to read {} for hashing: {}", file_path, e))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        Ok(hasher.finalize())
+    }
+
+    /// Changes into DBI's virtual install folder for the target. The folders carry a
+    /// menu number prefix that differs between DBI versions, so match on the suffix.
+    fn enter_install_folder(
+        ftp: &mut FtpConnection,
+        target: InstallTarget,
+    ) -> Result<(), TransferError> {
+        let folder_name = target.folder_name().to_lowercase();
+        let entries = ftp_browser::list_dir(ftp, "/").map_err(TransferError::Transient)?;
+
+        let folder = entries
+            .iter()
+            .find(|entry| {
+                entry.kind == RemoteEntryKind::Directory
+                    && entry.name.to_lowercase().ends_with(&folder_name)
+            })
+            .ok_or_else(|| {
+                TransferError::Fatal(format!(
+                    "Install folder \"{}\" not found on the console, this DBI version may not support it",
+                    target.folder_name()
+                ))
+            })?;
+
+        ftp.cwd(&folder.path).map_err(|e| {
+            TransferError::Transient(format!("Failed to enter {}: {}", folder.path, e))
+        })?;
+        debug!("Uploading into {}", folder.path);
+        Ok(())
+    }
+
+    /// Emits a file's progress and, for bundle members, the progress of the whole bundle.
+    /// The task must not be in the queue when this is called.
+    fn emit_progress(ctx: &TransferContext, task: &TransferTask, progress: &TransferProgress) {
+        let _ = ctx.app_handle.emit("ftp-transfer-progress", progress);
+
+        let Some(bundle) = task.bundle.as_ref() else {
+            return;
+        };
+
+        // Members still queued are either waiting or failed for good; whatever else left
+        // the queue, other than this file, was uploaded. Bundles are queued to a single
+        // console, so this counts only its own tasks.
+        let ((queued_files, queued_bytes), (failed_files, failed_bytes)) = ctx
+            .transfer_queue
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|queued| {
+                queued
+                    .bundle
+                    .as_ref()
+                    .map_or(false, |b| b.bundle_id == bundle.bundle_id)
+            })
+            .fold(
+                ((0usize, 0u64), (0usize, 0u64)),
+                |(queued, failed), member| {
+                    let size = member.game_file.file_size;
+                    if member.status == TransferStatus::Failed {
+                        (queued, (failed.0 + 1, failed.1 + size))
+                    } else {
+                        ((queued.0 + 1, queued.1 + size), failed)
+                    }
+                },
+            );
+
+        let file_done = progress.status == TransferStatus::Completed;
+        let file_failed = progress.status == TransferStatus::Failed;
+        let files_failed = failed_files + usize::from(file_failed);
+        let files_completed = bundle
+            .total_files
+            .saturating_sub(queued_files + failed_files + 1)
+            + usize::from(file_done);
+        let bytes_transferred = bundle
+            .total_bytes
+            .saturating_sub(queued_bytes + failed_bytes + task.game_file.file_size)
+            + if file_failed {
+                0
+            } else {
+                progress.bytes_transferred
+            };
+
+        // The bundle is over once nothing is left to send and this file has stopped.
+        let finished = queued_files == 0
+            && matches!(
+                progress.status,
+                TransferStatus::Completed | TransferStatus::Failed | TransferStatus::Cancelled
+            );
+        let incomplete = files_failed > 0 || !file_done;
+
+        let status = if finished && incomplete {
+            TransferStatus::Failed
+        } else if (file_done || file_failed) && queued_files > 0 {
+            TransferStatus::Transferring
+        } else {
+            progress.status.clone()
+        };
+
+        let bundle_progress = BundleProgress {
+            bundle_id: bundle.bundle_id.clone(),
+            device_id: task.device_id.clone(),
+            game_id: task.game_file.game_id,
+            game_title: task.game_file.game_title.clone(),
+            current_file: task.game_file.file_name.clone(),
+            current_kind: bundle.kind,
+            files_completed,
+            files_failed,
+            total_files: bundle.total_files,
+            bytes_transferred,
+            total_bytes: bundle.total_bytes,
+            progress_percent: if bundle.total_bytes > 0 {
+                bytes_transferred as f64 / bundle.total_bytes as f64 * 100.0
+            } else {
+                0.0
+            },
+            transfer_speed: progress.transfer_speed,
+            eta_seconds: if progress.transfer_speed > 0.0 {
+                (bundle.total_bytes.saturating_sub(bytes_transferred) as f64
+                    / progress.transfer_speed)
+                    .ceil() as u64
+            } else {
+                0
+            },
+            status,
+        };
+        let _ = ctx.app_handle.emit("ftp-bundle-progress", &bundle_progress);
+
+        if finished {
+            if incomplete {
+                warn!(
+                    "Bundle {} finished with {} of {} files sent",
+                    bundle.bundle_id, files_completed, bundle.total_files
+                );
+                let _ = ctx.app_handle.emit("ftp-bundle-failed", &bundle_progress);
+            } else {
+                info!("Bundle {} completed", bundle.bundle_id);
+                let _ = ctx.app_handle.emit("ftp-bundle-complete", &bundle_progress);
+            }
+        }
+    }
+
+    fn record_failure(task: &mut TransferTask, error: String, transient: bool) {
+        task.failures.push(TransferFailure {
+            attempt: task.attempts,
+            error,
+            transient,
+            failed_at: chrono::Local::now().to_rfc3339(),
+        });
+    }
 
-        // Create a memory cursor for the file upload
-        let mut file_contents = Vec::new();
-        file.read_to_end(&mut file_contents)
-            .map_err(|e| format!("Failed to read file: {}", e))?;
-        let mut cursor = Cursor::new(file_contents);
-
-        // Start upload using SuppaFTP's put_file
-        ftp.put_file(&game_file.file_name, &mut cursor)
-            .map_err(|e| format!("FTP upload failed: {}", e))?;
-
-        // Since SuppaFTP's put_file uploads all at once, we manually emit "completed" progress
-        bytes_transferred.store(game_file.file_size, Ordering::SeqCst);
-
-        let progress = TransferProgress {
-            game_id: game_file.game_id,
-            file_name: game_file.file_name.clone(),
-            bytes_transferred: game_file.file_size,
-            total_bytes: game_file.file_size,
-            progress_percent: 100.0,
-            transfer_speed: (game_file.file_size as f64) / start_time.elapsed().as_secs_f64(),
-            eta_seconds: 0,
-            status: TransferStatus::Completed,
+    fn build_progress(
+        task: &TransferTask,
+        bytes_transferred: u64,
+        total_bytes: u64,
+        transfer_speed: f64,
+        status: TransferStatus,
+    ) -> TransferProgress {
+        let progress_percent = if total_bytes > 0 {
+            (bytes_transferred as f64 / total_bytes as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let eta_seconds = if transfer_speed > 0.0 {
+            (total_bytes.saturating_sub(bytes_transferred) as f64 / transfer_speed).ceil() as u64
+        } else {
+            0
+        };
+
+        TransferProgress {
+            game_id: task.game_file.game_id,
+            file_name: task.game_file.file_name.clone(),
+            bytes_transferred,
+            total_bytes,
+            progress_percent,
+            transfer_speed,
+            eta_seconds,
+            status,
+            attempt: task.attempts,
+            device_id: task.device_id.clone(),
+        }
+    }
+
+    /// Measures the link to a console step by step: TCP connect, login, then an upload of
+    /// `test_size` bytes generated in memory, so the local disk plays no part. The test file
+    /// is removed afterwards. A failing step ends the run and is reported in `error`.
+    pub fn diagnose_connection(
+        profile: &FtpConnectionProfile,
+        test_size: u64,
+    ) -> ConnectionDiagnostics {
+        let mut report = ConnectionDiagnostics {
+            address: profile.address(),
+            use_ftps: profile.use_ftps,
+            tcp_connect_ms: None,
+            login_ms: None,
+            upload_bytes: 0,
+            upload_secs: None,
+            upload_speed: None,
+            test_file_removed: false,
+            transfers_active: false,
+            error: None,
+            tested_at: chrono::Local::now().to_rfc3339(),
+        };
+        info!("Running connection diagnostics for {}", report.address);
+
+        let addr = match profile.socket_addr() {
+            Ok(addr) => addr,
+            Err(e) => {
+                report.error = Some(e.to_string());
+                return report;
+            }
         };
 
-        let _ = app_handle.emit("ftp-transfer-progress", &progress);
+        let mut connect_total = Duration::ZERO;
+        for _ in 0..DIAGNOSTIC_TCP_SAMPLES {
+            let started = Instant::now();
+            if let Err(e) = std::net::TcpStream::connect_timeout(
+                &addr,
+                Duration::from_millis(ftp_connection::FTP_TIMEOUT_MS),
+            ) {
+                report.error = Some(format!(
+                    "Cannot connect to FTP server at {}: {}",
+                    report.address, e
+                ));
+                return report;
+            }
+            connect_total += started.elapsed();
+        }
+        report.tcp_connect_ms =
+            Some(connect_total.as_secs_f64() * 1000.0 / DIAGNOSTIC_TCP_SAMPLES as f64);
+
+        // Includes the TCP connect and, for FTPS, the TLS handshake
+        let started = Instant::now();
+        let mut ftp = match profile.connect() {
+            Ok(ftp) => ftp,
+            Err(e) => {
+                report.error = Some(e.to_string());
+                return report;
+            }
+        };
+        report.login_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+
+        let test_file = format!(
+            ".shard-speedtest-{}.bin",
+            chrono::Local::now().timestamp_millis()
+        );
+        let buffer = vec![0xA5u8; (TRANSFER_THROUGHPUT_BUFFER * 1024) as usize];
+
+        let upload = (|| -> Result<(), String> {
+            let mut stream = ftp
+                .put_with_stream(&test_file)
+                .map_err(|e| format!("Failed to open FTP data stream: {}", e))?;
+
+            let started = Instant::now();
+            let mut remaining = test_size;
+            while remaining > 0 {
+                let chunk = remaining.min(buffer.len() as u64) as usize;
+                stream
+                    .write_all(&buffer[..chunk])
+                    .map_err(|e| format!("FTP upload failed: {}", e))?;
+                remaining -= chunk as u64;
+                report.upload_bytes += chunk as u64;
+            }
+
+            ftp.finalize_put_stream(stream)
+                .map_err(|e| format!("Failed to finalize FTP upload: {}", e))?;
+
+            let secs = started.elapsed().as_secs_f64().max(f64::EPSILON);
+            report.upload_secs = Some(secs);
+            report.upload_speed = Some(report.upload_bytes as f64 / secs);
+            Ok(())
+        })();
+        if let Err(e) = upload {
+            report.error = Some(e);
+        }
 
-        ftp.quit().ok(); // ignore quit errors
+        // Also clean up after a failed upload, which may have left part of the file
+        if report.upload_bytes > 0 {
+            match ftp.rm(&test_file) {
+                Ok(_) => report.test_file_removed = true,
+                Err(e) => warn!("Failed to remove speed test file {}: {}", test_file, e),
+            }
+        }
+        ftp.quit().ok();
 
         info!(
-            "FTP transfer completed: {} ({} bytes in {:.2}s)",
-            game_file.file_name,
-            game_file.file_size,
-            start_time.elapsed().as_secs_f64()
+            "Diagnostics for {}: connect {:?} ms, login {:?} ms, upload {:?} bytes/s",
+            report.address, report.tcp_connect_ms, report.login_ms, report.upload_speed
         );
-
-        Ok(())
+        report
     }
 
+    /// Progress of the first transfer in flight, kept for callers that expect one console.
     pub fn get_current_transfer(&self) -> Option<TransferProgress> {
-        self.current_transfer.lock().unwrap().clone()
+        self.get_current_transfers().into_iter().next()
+    }
+
+    /// Progress of every transfer in flight, one per console.
+    pub fn get_current_transfers(&self) -> Vec<TransferProgress> {
+        self.workers()
+            .iter()
+            .filter_map(|worker| worker.current_transfer.lock().unwrap().clone())
+            .collect()
     }
 
     pub fn is_transferring(&self) -> bool {
-        self.is_transferring.load(Ordering::SeqCst)
+        self.workers()
+            .iter()
+            .any(|worker| worker.is_transferring.load(Ordering::SeqCst))
     }
 }
 
+/// Whether a task uploads `file_path`, to the given console or to any without one.
+fn task_matches(task: &TransferTask, file_path: &str, device_id: Option<&str>) -> bool {
+    task.game_file.file_path == file_path
+        && device_id.map_or(true, |device_id| {
+            task.device_id.as_deref() == Some(device_id)
+        })
+}
+
 // Event payloads
 #[derive(Clone, Serialize)]
 struct QueueUpdatePayload {
@@ -536,13 +2753,121 @@ struct QueueUpdatePayload {
 struct TransferCompletePayload {
     game_id: u32,
     file_name: String,
+    device_id: Option<String>,
+}
+
+/// Emitted after a finished download has been handed to the transfer queue, or failed to be.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoInstallPayload {
+    pub game_id: u32,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+    pub bundle: Option<GameBundle>,
+    pub error: Option<String>,
+}
+
+/// Hooks the torrent `download-complete` event up to auto-install. Needs the manager
+/// to be in managed state already. Only downloads that actually ran this session count,
+/// not torrents that report themselves complete again after a restore or verify.
+pub fn listen_for_completed_downloads(app_handle: &AppHandle) {
+    let handle = app_handle.clone();
+
+    app_handle.listen("download-complete", move |event| {
+        let payload = serde_json::from_str::<serde_json::Value>(event.payload()).ok();
+        let game_id = payload
+            .as_ref()
+            .and_then(|payload| payload.get("gameId").and_then(|id| id.as_u64()));
+        let Some(game_id) = game_id else {
+            warn!("Ignoring download-complete event without a game id");
+            return;
+        };
+
+        let live = payload
+            .as_ref()
+            .and_then(|payload| payload.get("live").and_then(|live| live.as_bool()))
+            .unwrap_or(false);
+        if !live {
+            debug!(
+                "Game {} was already complete, not auto-installing it",
+                game_id
+            );
+            return;
+        }
+
+        // Scanning the game folder touches the disk, keep it off the emitting task
+        let handle = handle.clone();
+        thread::spawn(move || {
+            let Some(state) = handle.try_state::<Arc<parking_lot::Mutex<Option<FTPManager>>>>()
+            else {
+                return;
+            };
+
+            let payload = match state.lock().as_ref() {
+                Some(manager) => manager.auto_install_download(game_id as u32),
+                None => None,
+            };
+
+            if let Some(payload) = payload {
+                let _ = handle.emit("download-auto-install", &payload);
+            }
+        });
+    });
+}
+
+/// Aggregate progress of a bundle, emitted alongside each file's own progress.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleProgress {
+    pub bundle_id: String,
+    pub device_id: Option<String>,
+    pub game_id: u32,
+    pub game_title: String,
+    pub current_file: String,
+    pub current_kind: ContentKind,
+    pub files_completed: usize,
+    /// Members that failed for good and are waiting in the queue to be retried.
+    pub files_failed: usize,
+    pub total_files: usize,
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub progress_percent: f64,
+    pub transfer_speed: f64,
+    pub eta_seconds: u64,
+    pub status: TransferStatus,
+}
+
+/// Result of `diagnose_ftp_connection`. Timings are None for steps that did not run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnostics {
+    pub address: String,
+    pub use_ftps: bool,
+    /// Average time to open a TCP connection, in milliseconds.
+    pub tcp_connect_ms: Option<f64>,
+    /// Time to connect, negotiate TLS if enabled and log in, in milliseconds.
+    pub login_ms: Option<f64>,
+    pub upload_bytes: u64,
+    pub upload_secs: Option<f64>,
+    /// Upload throughput in bytes per second.
+    pub upload_speed: Option<f64>,
+    pub test_file_removed: bool,
+    /// Whether transfers were running during the test and sharing the link.
+    pub transfers_active: bool,
+    pub error: Option<String>,
+    /// RFC 3339 timestamp of the test.
+    pub tested_at: String,
 }
 
 #[derive(Clone, Serialize)]
 struct TransferErrorPayload {
     game_id: u32,
     file_name: String,
+    device_id: Option<String>,
     error: String,
+    attempt: u32,
+    /// Whether the task goes back into the queue for another attempt.
+    retrying: bool,
 }
 
 // Tauri commands
@@ -574,6 +2899,98 @@ pub fn get_ftp_ip(
     }
 }
 
+#[tauri::command]
+pub fn set_ftp_profile(
+    profile: FtpConnectionProfile,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        let address = profile.address();
+        manager.set_ftp_profile(profile)?;
+        Ok(format!("FTP profile set to {}", address))
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_ftp_profile(
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<Option<FtpConnectionProfile>, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        Ok(manager.get_ftp_profile())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_transfer_settings(
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<TransferSettings, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        Ok(manager.get_settings())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_transfer_settings(
+    settings: TransferSettings,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.set_settings(settings)?;
+        Ok("Transfer settings updated".to_string())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_ftp_upload_limit(
+    upload_limit: Option<u64>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.set_upload_limit(upload_limit)?;
+        Ok(match upload_limit.filter(|limit| *limit > 0) {
+            Some(limit) => format!("Upload limit set to {} bytes/s", limit),
+            None => "Upload limit removed".to_string(),
+        })
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_task_upload_limit(
+    file_path: String,
+    device_id: Option<String>,
+    upload_limit: Option<u64>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.set_task_upload_limit(&file_path, device_id.as_deref(), upload_limit)?;
+        Ok(format!("Upload limit updated for {}", file_path))
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn scan_game_files(
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
@@ -590,18 +3007,64 @@ pub fn scan_game_files(
 #[tauri::command]
 pub fn queue_file(
     game_file: GameFile,
+    device_id: Option<String>,
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
 ) -> Result<String, String> {
     let manager_guard = state.lock();
 
     if let Some(manager) = manager_guard.as_ref() {
-        manager.queue_file(game_file.clone())?;
+        manager.queue_file(game_file.clone(), device_id)?;
         Ok(format!("File {} queued for transfer", game_file.file_name))
     } else {
         Err("FTP Manager not initialized".to_string())
     }
 }
 
+#[tauri::command]
+pub fn queue_file_to_devices(
+    game_file: GameFile,
+    device_ids: Vec<String>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<Vec<DeviceQueueResult>, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.queue_file_to_devices(game_file, device_ids)
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn scan_game_bundle(
+    game_id: u32,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<GameBundle, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.scan_game_bundle(game_id)
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn queue_game_bundle(
+    game_id: u32,
+    device_id: Option<String>,
+    install_target: Option<InstallTarget>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<GameBundle, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.queue_bundle(game_id, device_id, install_target)
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn get_transfer_queue(
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
@@ -615,6 +3078,19 @@ pub fn get_transfer_queue(
     }
 }
 
+#[tauri::command]
+pub fn get_transfer_tasks(
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<Vec<TransferTask>, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        Ok(manager.get_queue_tasks())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn clear_transfer_queue(
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
@@ -632,18 +3108,94 @@ pub fn clear_transfer_queue(
 #[tauri::command]
 pub fn remove_from_transfer_queue(
     file_path: String,
+    device_id: Option<String>,
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
 ) -> Result<String, String> {
     let manager_guard = state.lock();
 
     if let Some(manager) = manager_guard.as_ref() {
-        manager.remove_from_queue(&file_path)?;
+        manager.remove_from_queue(&file_path, device_id.as_deref())?;
         Ok(format!("File removed from queue: {}", file_path))
     } else {
         Err("FTP Manager not initialized".to_string())
     }
 }
 
+#[tauri::command]
+pub fn retry_failed_transfers(
+    file_path: Option<String>,
+    device_id: Option<String>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        let retried = manager.retry_failed_transfers(file_path.as_deref(), device_id.as_deref())?;
+        Ok(format!("Retrying {} failed transfer(s)", retried))
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn clear_failed_transfers(
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        let cleared = manager.clear_failed_transfers();
+        Ok(format!("Cleared {} failed transfer(s)", cleared))
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn pause_ftp_transfer(
+    device_id: Option<String>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.pause_transfer(device_id.as_deref())?;
+        Ok("Transfer paused".to_string())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn resume_ftp_transfer(
+    device_id: Option<String>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.resume_transfer(device_id.as_deref())?;
+        Ok("Transfer resumed".to_string())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn cancel_ftp_transfer(
+    device_id: Option<String>,
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<String, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.cancel_transfer(device_id.as_deref())?;
+        Ok("Transfer cancelled".to_string())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn get_current_transfer(
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
@@ -657,6 +3209,46 @@ pub fn get_current_transfer(
     }
 }
 
+#[tauri::command]
+pub fn get_current_transfers(
+    state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<Vec<TransferProgress>, String> {
+    let manager_guard = state.lock();
+
+    if let Some(manager) = manager_guard.as_ref() {
+        Ok(manager.get_current_transfers())
+    } else {
+        Err("FTP Manager not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn diagnose_ftp_connection(
+    device_id: Option<String>,
+    test_size_mb: Option<u64>,
+    registry: tauri::State<'_, Arc<DeviceRegistry>>,
+    state: tauri::State<'_, Arc<parking_lot::Mutex<Option<FTPManager>>>>,
+) -> Result<ConnectionDiagnostics, String> {
+    let profile = ftp_browser::resolve_profile(device_id, &registry, &state)?;
+    let transfers_active = state
+        .lock()
+        .as_ref()
+        .map_or(false, |manager| manager.is_transferring());
+    let test_size = test_size_mb
+        .unwrap_or(DEFAULT_DIAGNOSTIC_SIZE_MB)
+        .clamp(1, MAX_DIAGNOSTIC_SIZE_MB)
+        * 1024
+        * 1024;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut report = FTPManager::diagnose_connection(&profile, test_size);
+        report.transfers_active = transfers_active;
+        report
+    })
+    .await
+    .map_err(|e| format!("FTP task failed: {}", e))
+}
+
 #[tauri::command]
 pub fn is_ftp_transferring(
     state: tauri::State<Arc<parking_lot::Mutex<Option<FTPManager>>>>,
@@ -666,3 +3258,139 @@ pub fn is_ftp_transferring(
         .as_ref()
         .map_or(false, |m| m.is_transferring())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_per_attempt() {
+        let settings = TransferSettings::default();
+        assert_eq!(settings.retry_delay(1), Duration::from_secs(5));
+        assert_eq!(settings.retry_delay(2), Duration::from_secs(10));
+        assert_eq!(settings.retry_delay(3), Duration::from_secs(20));
+        // Attempts are 1-based, a zero is treated as the first
+        assert_eq!(settings.retry_delay(0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let settings = TransferSettings::default();
+        assert_eq!(settings.retry_delay(6), Duration::from_secs(120));
+        assert_eq!(settings.retry_delay(u32::MAX), Duration::from_secs(120));
+
+        let settings = TransferSettings {
+            retry_delay_secs: u64::MAX,
+            max_retry_delay_secs: 300,
+            ..TransferSettings::default()
+        };
+        assert_eq!(settings.retry_delay(40), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn content_kind_reads_the_title_id() {
+        assert_eq!(
+            ContentKind::from_file_name("Some Game [0100ABCD12340000][v0].nsp"),
+            ContentKind::Base
+        );
+        assert_eq!(
+            ContentKind::from_file_name("Some Game [0100ABCD12340800][v131072].nsp"),
+            ContentKind::Update
+        );
+        assert_eq!(
+            ContentKind::from_file_name("Some Game Costume Pack [0100ABCD12341001][v0].nsp"),
+            ContentKind::Dlc
+        );
+        // Lower case ids and ids that are not in brackets work too
+        assert_eq!(
+            ContentKind::from_file_name("0100abcd12340800.nsz"),
+            ContentKind::Update
+        );
+    }
+
+    #[test]
+    fn content_kind_prefers_the_title_id_over_keywords() {
+        assert_eq!(
+            ContentKind::from_file_name("Update Pack [0100ABCD12340000].nsp"),
+            ContentKind::Base
+        );
+    }
+
+    #[test]
+    fn content_kind_falls_back_to_keywords() {
+        assert_eq!(
+            ContentKind::from_file_name("Some Game [UPD].nsp"),
+            ContentKind::Update
+        );
+        assert_eq!(
+            ContentKind::from_file_name("Some Game Update 1.2.nsp"),
+            ContentKind::Update
+        );
+        assert_eq!(
+            ContentKind::from_file_name("Some Game DLC.nsp"),
+            ContentKind::Dlc
+        );
+        assert_eq!(
+            ContentKind::from_file_name("Some Game.xci"),
+            ContentKind::Base
+        );
+    }
+
+    #[test]
+    fn effective_limit_takes_the_stricter_one() {
+        assert_eq!(RateLimiter::effective_limit(None, None), None);
+        assert_eq!(RateLimiter::effective_limit(Some(1000), None), Some(1000));
+        assert_eq!(RateLimiter::effective_limit(None, Some(500)), Some(500));
+        assert_eq!(
+            RateLimiter::effective_limit(Some(1000), Some(500)),
+            Some(500)
+        );
+        assert_eq!(
+            RateLimiter::effective_limit(Some(200), Some(500)),
+            Some(200)
+        );
+        // A zero limit means unlimited
+        assert_eq!(RateLimiter::effective_limit(Some(0), None), None);
+    }
+
+    #[test]
+    fn chunk_size_follows_the_limit() {
+        let buffer_len = 1024 * 1024;
+        assert_eq!(RateLimiter::chunk_size(None, buffer_len), buffer_len);
+        assert_eq!(
+            RateLimiter::chunk_size(Some(1024 * 1024), buffer_len),
+            128 * 1024
+        );
+        assert_eq!(
+            RateLimiter::chunk_size(Some(1000), buffer_len),
+            MIN_THROTTLED_CHUNK
+        );
+        assert_eq!(
+            RateLimiter::chunk_size(Some(u64::MAX), buffer_len),
+            buffer_len
+        );
+    }
+
+    #[test]
+    fn reserve_waits_for_bytes_over_the_limit() {
+        let mut limiter = RateLimiter::new();
+        assert_eq!(limiter.reserve(None, 1_000_000), Duration::ZERO);
+
+        // Half a second worth of bytes, booked at once
+        let wait = limiter.reserve(Some(1000), 500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+
+        // The window keeps counting, so the next booking waits for both
+        let wait = limiter.reserve(Some(1000), 500);
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reserve_restarts_the_window_when_the_limit_changes() {
+        let mut limiter = RateLimiter::new();
+        limiter.reserve(Some(1000), 1000);
+
+        let wait = limiter.reserve(Some(10_000), 1000);
+        assert!(wait <= Duration::from_millis(100));
+    }
+}
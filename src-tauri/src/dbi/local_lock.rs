@@ -0,0 +1,200 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Local files and folders in use by a transfer, keyed by canonical path.
+static LOCAL_FILE_LOCKS: Lazy<Mutex<HashMap<PathBuf, LockState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, Default)]
+struct LockState {
+    readers: usize,
+    writing: bool,
+}
+
+/// Holds a local file, or a whole folder, until dropped. Any number of uploads may read
+/// the same file, but nothing reads it while it is written and only one writer at a time.
+/// A folder lock covers everything inside it, so deleting a game waits for its uploads.
+pub struct LocalFileLock {
+    key: PathBuf,
+    write: bool,
+}
+
+impl LocalFileLock {
+    /// Locks a path for reading, e.g. to send it to a console.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        Self::acquire(path, false)
+    }
+
+    /// Locks a path for writing or deleting.
+    pub fn write(path: &Path) -> Result<Self, String> {
+        Self::acquire(path, true)
+    }
+
+    fn acquire(path: &Path, write: bool) -> Result<Self, String> {
+        let key = lock_key(path);
+        let mut locks = LOCAL_FILE_LOCKS.lock().unwrap();
+
+        let conflict = locks.iter().find(|(held, state)| {
+            let overlaps = held.starts_with(&key) || key.starts_with(held.as_path());
+            overlaps && (state.writing || (write && state.readers > 0))
+        });
+        if let Some((held, state)) = conflict {
+            return Err(if state.writing {
+                format!("{} is still being written", held.display())
+            } else {
+                format!("{} is being uploaded", held.display())
+            });
+        }
+
+        let state = locks.entry(key.clone()).or_default();
+        if write {
+            state.writing = true;
+        } else {
+            state.readers += 1;
+        }
+        Ok(Self { key, write })
+    }
+}
+
+impl Drop for LocalFileLock {
+    fn drop(&mut self) {
+        let mut locks = LOCAL_FILE_LOCKS.lock().unwrap();
+        let Some(state) = locks.get_mut(&self.key) else {
+            return;
+        };
+
+        if self.write {
+            state.writing = false;
+        } else {
+            state.readers = state.readers.saturating_sub(1);
+        }
+        if !state.writing && state.readers == 0 {
+            locks.remove(&self.key);
+        }
+    }
+}
+
+/// Whether the file, or a folder it is in, is being written right now.
+pub fn is_being_written(path: &Path) -> bool {
+    let key = lock_key(path);
+    LOCAL_FILE_LOCKS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(held, state)| state.writing && key.starts_with(held))
+}
+
+/// Canonical form of a path so `a/../b`, relative paths and symlinks all lock the same
+/// file. A file that does not exist yet is keyed by its canonical folder.
+fn lock_key(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh folder per test, as the locks are shared by every test in the process.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nx-shard-lock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("game")).unwrap();
+        fs::write(dir.join("game").join("base.nsp"), b"base").unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn lock_key_resolves_relative_parts() {
+        let dir = scratch_dir("key");
+        let file = dir.join("game").join("base.nsp");
+        assert_eq!(
+            lock_key(&dir.join("game").join("..").join("game").join("base.nsp")),
+            file
+        );
+        assert_eq!(lock_key(&file), file);
+    }
+
+    #[test]
+    fn lock_key_keys_missing_files_by_their_folder() {
+        let dir = scratch_dir("missing");
+        let messy = dir.join("game").join(".").join("update.nsp");
+        assert_eq!(lock_key(&messy), dir.join("game").join("update.nsp"));
+    }
+
+    #[test]
+    fn readers_share_a_file() {
+        let dir = scratch_dir("readers");
+        let file = dir.join("game").join("base.nsp");
+        let first = LocalFileLock::read(&file).unwrap();
+        let second = LocalFileLock::read(&file).unwrap();
+        assert!(LocalFileLock::write(&file).is_err());
+        assert!(!is_being_written(&file));
+
+        drop(first);
+        assert!(LocalFileLock::write(&file).is_err());
+        drop(second);
+        assert!(LocalFileLock::write(&file).is_ok());
+    }
+
+    #[test]
+    fn a_writer_excludes_everyone() {
+        let dir = scratch_dir("writer");
+        let file = dir.join("game").join("base.nsp");
+        let writer = LocalFileLock::write(&file).unwrap();
+        assert!(is_being_written(&file));
+        assert!(LocalFileLock::read(&file).is_err());
+        assert!(LocalFileLock::write(&file).is_err());
+
+        drop(writer);
+        assert!(!is_being_written(&file));
+        assert!(LocalFileLock::read(&file).is_ok());
+    }
+
+    #[test]
+    fn folder_locks_cover_their_files() {
+        let dir = scratch_dir("folder");
+        let folder = dir.join("game");
+        let file = folder.join("base.nsp");
+
+        // Deleting the game waits for its uploads
+        let reader = LocalFileLock::read(&file).unwrap();
+        assert!(LocalFileLock::write(&folder).is_err());
+        assert!(LocalFileLock::write(&dir).is_err());
+        drop(reader);
+
+        // Nothing in the game is read while it is rewritten
+        let writer = LocalFileLock::write(&folder).unwrap();
+        assert!(is_being_written(&file));
+        assert!(LocalFileLock::read(&file).is_err());
+        assert!(LocalFileLock::read(&dir).is_err());
+        drop(writer);
+
+        // Reading the folder and a file in it at once is fine
+        let folder_reader = LocalFileLock::read(&folder).unwrap();
+        assert!(LocalFileLock::read(&file).is_ok());
+        drop(folder_reader);
+    }
+
+    #[test]
+    fn unrelated_paths_do_not_conflict() {
+        let dir = scratch_dir("unrelated");
+        fs::create_dir_all(dir.join("game2")).unwrap();
+        let _writer = LocalFileLock::write(&dir.join("game")).unwrap();
+        assert!(LocalFileLock::write(&dir.join("game2")).is_ok());
+        // A sibling whose name starts the same is not inside the folder
+        assert!(LocalFileLock::read(&dir.join("game2").join("base.nsp")).is_ok());
+    }
+}
@@ -1,2 +1,6 @@
+pub mod device_registry;
+pub mod ftp_browser;
+pub mod ftp_connection;
 pub mod ftp_discovery;
 pub mod ftp_manager;
+pub mod local_lock;
@@ -6,7 +6,7 @@ mod torrent;
 
 use anyhow::Context;
 use directories::{BaseDirs, ProjectDirs, UserDirs};
-use librqbit::{AddTorrent, AddTorrentOptions, AddTorrentResponse, ManagedTorrent, Session};
+use librqbit::{AddTorrentOptions, AddTorrentResponse, ManagedTorrent, Session};
 use log::{debug, error, info, warn};
 use parking_lot::Mutex;
 use reqwest;
@@ -22,9 +22,13 @@ use tauri::{Emitter, Listener, Manager, State};
 use tokio::sync::RwLock;
 use tracing_subscriber::{EnvFilter, Layer, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::dbi::{ftp_discovery, ftp_manager};
+use crate::dbi::{device_registry, ftp_browser, ftp_connection, ftp_discovery, ftp_manager};
+use crate::dbi::local_lock::LocalFileLock;
 
-use crate::torrent::state::TorrentState;
+use crate::torrent::state::{
+    DownloadPriority, QueuedDownload, RateLimits, TorrentFileEntry, TorrentSettings, TorrentSource,
+    TorrentState, VerifyReport, is_magnet_link,
+};
 
 use crate::configs::constants::{APP_PATH, CONFIG_PATH, GAME_PATH};
 use crate::configs::defaults::{get_app_path, get_config_path, get_game_path};
@@ -100,6 +104,18 @@ async fn obtain_torrent_file(invoke_message: GameMeta) -> Result<(), String> {
         ));
     }
 
+    // Magnet links have no file to fetch, keep the link for download_game instead
+    if is_magnet_link(&invoke_message.downloadUrl) {
+        let magnet_path = game_dir.join("game.magnet");
+        info!(
+            "[Shard_Torrent_Backend] Saving magnet link to {:?}",
+            magnet_path
+        );
+        fs::write(&magnet_path, invoke_message.downloadUrl.trim())
+            .map_err(|e| format!("Failed to write magnet link: {}", e))?;
+        return Ok(());
+    }
+
     let torrent_path = game_dir.join("game.torrent");
     info!(
         "[Shard_Torrent_Backend] Downloading torrent to {:?}",
@@ -128,6 +144,8 @@ async fn obtain_torrent_file(invoke_message: GameMeta) -> Result<(), String> {
 #[tauri::command]
 async fn download_game(
     invoke_message: GameMeta,
+    priority: Option<DownloadPriority>,
+    only_files: Option<Vec<usize>>,
     state: State<'_, TorrentState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
@@ -139,14 +157,17 @@ async fn download_game(
 
     let game_id = invoke_message.id;
     let game_dir = Path::new(&get_game_path()).join(game_id.to_string());
-    let torrent_path = game_dir.join("game.torrent");
 
-    // Check if torrent file exists
-    if !torrent_path.exists() {
+    // Check if a torrent file or magnet link exists
+    let Some(source) = TorrentSource::for_game_dir(&game_dir) else {
         return Err(format!(
             "Torrent file does not exist for game id {}",
             game_id
         ));
+    };
+
+    if only_files.as_ref().is_some_and(|files| files.is_empty()) {
+        return Err("No files selected for download".to_string());
     }
 
     // Check if already downloading
@@ -162,18 +183,28 @@ async fn download_game(
         game_id
     );
 
+    // A new download of a game that was auto-installed before should be installed again
+    if let Some(ftp) = app_handle.try_state::<Arc<Mutex<Option<ftp_manager::FTPManager>>>>() {
+        if let Some(manager) = ftp.lock().as_ref() {
+            manager.forget_auto_install(game_id);
+        }
+    }
+
     // Use the SHARED session instead of creating a new one
     let session = state.session.clone();
 
-    // Add the torrent to the session with output_folder set to game-specific directory
-    let torrent_path_str = torrent_path.to_string_lossy().to_string();
+    // Add the torrent to the session with output_folder set to game-specific directory.
+    // For magnet links this waits until the metadata has been fetched from peers.
     let (torrent_id, handle) = match session
         .add_torrent(
-            AddTorrent::from_local_filename(&torrent_path_str)
-                .map_err(|e| format!("Failed to read torrent file: {}", e))?,
+            source.to_add_torrent()?,
             Some(AddTorrentOptions {
+                // Stays paused until the download queue has a slot for it
+                paused: true,
                 overwrite: true,
                 output_folder: Some(game_dir.to_path_buf().to_string_lossy().to_string()),
+                ratelimits: state.game_rate_limits(game_id).await.to_config(),
+                only_files: only_files.clone(),
                 ..Default::default()
             }),
         )
@@ -206,6 +237,17 @@ async fn download_game(
         let mut handles = state.handles.write().await;
         handles.insert(game_id, (torrent_id, handle.clone()));
     }
+    // Stops the tasks below if the download is verified, removed or started again
+    let token = state.download_token(game_id).await;
+    state
+        .remember_download(
+            game_id,
+            &handle,
+            &source,
+            priority.unwrap_or_default(),
+            only_files,
+        )
+        .await;
 
     // Log metadata
     handle
@@ -214,12 +256,15 @@ async fn download_game(
         })
         .map_err(|e| format!("Failed to read metadata: {}", e))?;
 
-    // Start the torrent (unpause it to begin downloading)
-    info!("[Shard_Torrent_Backend] Starting torrent download...");
-    session
-        .unpause(&handle)
-        .await
-        .map_err(|e| format!("Failed to start torrent: {}", e))?;
+    // Start the torrent now, or queue it if too many downloads are running
+    if state.enqueue_or_start(game_id, &handle, &app_handle).await? {
+        info!("[Shard_Torrent_Backend] Starting torrent download...");
+    } else {
+        info!(
+            "[Shard_Torrent_Backend] Download for game {} queued",
+            game_id
+        );
+    }
 
     // Print stats periodically and emit to frontend
     let start_time = std::time::Instant::now();
@@ -227,9 +272,16 @@ async fn download_game(
         let handle = handle.clone();
         let app_handle = app_handle.clone();
         let state_handles = state.handles.clone();
+        let token = token.clone();
         async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(1)).await;
+
+                // A newer handle for the game is not ours to report on
+                if token.is_cancelled() {
+                    info!("[Shard_Torrent_Backend] Download of game {} was replaced, stopping progress monitor", game_id);
+                    break;
+                }
                 
                 // Check if torrent was removed from state (e.g., by clear_game_path)
                 {
@@ -315,15 +367,29 @@ async fn download_game(
 
     // Wait for completion in background
     let handle_clone = handle.clone();
-    let state_handles = state.handles.clone();
     let app_handle_clone = app_handle.clone();
+    let queue = state.queue();
     tokio::spawn(async move {
+        // The timeout only counts once the download has left the queue
+        tokio::select! {
+            _ = TorrentState::wait_while_queued(&queue, game_id) => {}
+            _ = token.cancelled() => return,
+        }
+
         let result = tokio::select! {
             result = handle_clone.wait_until_completed() => result,
             _ = tokio::time::sleep(Duration::from_secs(3600)) => {
                 Err(anyhow::anyhow!("Download timeout after 1 hour"))
             }
+            _ = token.cancelled() => {
+                info!(
+                    "[Shard_Torrent_Backend] Download of game {} was replaced, stopping completion task",
+                    game_id
+                );
+                return;
+            }
         };
+        let finished = result.is_ok();
 
         match result {
             Ok(_) => {
@@ -337,6 +403,7 @@ async fn download_game(
                     "download-complete",
                     serde_json::json!({
                         "gameId": game_id,
+                        "live": true,
                     }),
                 );
             }
@@ -353,14 +420,44 @@ async fn download_game(
             }
         }
 
-        // Remove handle
-        let mut handles = state_handles.write().await;
-        handles.remove(&game_id);
+        // Remove handle, unless a newer download of the game has taken its place
+        let Some(state) = app_handle_clone.try_state::<TorrentState>() else {
+            return;
+        };
+        if state.release_handle(game_id, &handle_clone).await && finished {
+            // Finished games are not restored or downloaded again
+            state.forget_download(game_id).await;
+        }
     });
 
     Ok(())
 }
 
+#[tauri::command]
+async fn inspect_torrent(
+    invoke_message: GameMeta,
+    state: State<'_, TorrentState>,
+) -> Result<Vec<TorrentFileEntry>, String> {
+    let game_dir = Path::new(&get_game_path()).join(invoke_message.id.to_string());
+    let Some(source) = TorrentSource::for_game_dir(&game_dir) else {
+        return Err(format!(
+            "Torrent file does not exist for game id {}",
+            invoke_message.id
+        ));
+    };
+
+    state.list_files(&source).await
+}
+
+#[tauri::command]
+async fn verify_game_download(
+    invoke_message: GameMeta,
+    state: State<'_, TorrentState>,
+    app_handle: tauri::AppHandle,
+) -> Result<VerifyReport, String> {
+    state.verify(invoke_message.id, &app_handle).await
+}
+
 #[tauri::command]
 async fn pause_game(
     invoke_message: GameMeta,
@@ -371,13 +468,11 @@ async fn pause_game(
 
     if let Some((_torrent_id, handle)) = handles.get(&game_id) {
         let handle = handle.clone();
-        let session = state.session.clone();
         drop(handles); // Release lock before async operation
 
-        session
-            .pause(&handle)
-            .await
-            .map_err(|e| format!("Failed to pause: {}", e))?;
+        // A paused game no longer waits for a slot; queued ones are paused already
+        state.dequeue(game_id).await;
+        state.pause_if_live(&handle).await?;
         info!(
             "[Shard_Torrent_Backend] Paused download for game id {}",
             game_id
@@ -401,6 +496,8 @@ async fn resume_game(
         let session = state.session.clone();
         drop(handles); // Release lock before async operation
 
+        // Resuming by hand skips the queue
+        state.dequeue(game_id).await;
         session
             .unpause(&handle)
             .await
@@ -422,17 +519,17 @@ async fn uninstall_game(
 ) -> Result<(), String> {
     let game_id = invoke_message.id;
 
+    // Refuse while any of the game's files is being sent to a console
+    let game_dir = Path::new(&get_game_path()).join(game_id.to_string());
+    let _lock = LocalFileLock::write(&game_dir)?;
+
     // First, try to remove the torrent if it's active
     if let Some((torrent_id, handle)) = {
         let handles = state.handles.read().await;
         handles.get(&game_id).cloned()
     } {
-        // Pause torrent (safe)
-        state
-            .session
-            .pause(&handle)
-            .await
-            .map_err(|e| format!("Failed to pause torrent: {}", e))?;
+        // Pause torrent (safe), unless it is queued and paused already
+        state.pause_if_live(&handle).await?;
 
         // Remove from session
         state
@@ -456,8 +553,10 @@ async fn uninstall_game(
         );
     }
 
+    // Don't restore the download on the next start
+    state.forget_game(game_id).await;
+
     // Now, delete game files
-    let game_dir = Path::new(&get_game_path()).join(game_id.to_string());
     if game_dir.exists() {
         fs::remove_dir_all(&game_dir).map_err(|e| format!("Failed to delete game files: {}", e))?;
         info!(
@@ -491,6 +590,13 @@ fn extract_and_clean(invoke_message: GameMeta) -> Result<(), String> {
         warn!("[Shard_Torrent_Backend] Torrent file does not exist, nothing to remove.");
     }
 
+    // The saved state keeps its own copy of the magnet link
+    let magnet_path = torrent_path.with_file_name("game.magnet");
+    if magnet_path.exists() {
+        fs::remove_file(&magnet_path)
+            .map_err(|e| format!("Failed to remove magnet link: {}", e))?;
+    }
+
     Ok(())
 }
 
@@ -498,6 +604,8 @@ fn extract_and_clean(invoke_message: GameMeta) -> Result<(), String> {
 async fn get_active_downloads(
     state: State<'_, TorrentState>,
 ) -> Result<Vec<serde_json::Value>, String> {
+    let queue = state.queue();
+    let queue = queue.read().await;
     let handles = state.handles.read().await;
     let mut active_downloads = Vec::new();
 
@@ -509,6 +617,7 @@ async fn get_active_downloads(
         active_downloads.push(serde_json::json!({
             "gameId": *game_id,
             "state": format!("{:?}", stats.state),
+            "queued": queue.contains(game_id),
             "progress": progress_percent,
             "downloadedBytes": stats.progress_bytes,
             "totalBytes": stats.total_bytes,
@@ -518,6 +627,72 @@ async fn get_active_downloads(
     Ok(active_downloads)
 }
 
+#[tauri::command]
+async fn get_download_queue(state: State<'_, TorrentState>) -> Result<Vec<QueuedDownload>, String> {
+    Ok(state.get_queue().await)
+}
+
+#[tauri::command]
+async fn set_download_priority(
+    game_id: u32,
+    priority: DownloadPriority,
+    state: State<'_, TorrentState>,
+) -> Result<(), String> {
+    state.set_priority(game_id, priority).await
+}
+
+#[tauri::command]
+async fn reorder_download(
+    game_id: u32,
+    position: usize,
+    state: State<'_, TorrentState>,
+) -> Result<(), String> {
+    state.move_in_queue(game_id, position).await
+}
+
+#[tauri::command]
+async fn move_download_to_top(game_id: u32, state: State<'_, TorrentState>) -> Result<(), String> {
+    state.move_in_queue(game_id, 0).await
+}
+
+#[tauri::command]
+async fn start_next_download(
+    state: State<'_, TorrentState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<u32>, String> {
+    state.start_next(&app_handle).await
+}
+
+#[tauri::command]
+async fn get_torrent_settings(state: State<'_, TorrentState>) -> Result<TorrentSettings, String> {
+    Ok(state.get_settings().await)
+}
+
+#[tauri::command]
+async fn set_torrent_settings(
+    settings: TorrentSettings,
+    state: State<'_, TorrentState>,
+) -> Result<(), String> {
+    state.set_settings(settings).await
+}
+
+#[tauri::command]
+async fn get_game_rate_limits(
+    game_id: u32,
+    state: State<'_, TorrentState>,
+) -> Result<RateLimits, String> {
+    Ok(state.game_rate_limits(game_id).await)
+}
+
+#[tauri::command]
+async fn set_game_rate_limits(
+    game_id: u32,
+    rate_limits: RateLimits,
+    state: State<'_, TorrentState>,
+) -> Result<(), String> {
+    state.set_game_rate_limits(game_id, rate_limits).await
+}
+
 // ------------------ SYSTEM INFO ------------------
 fn contains_game_file(dir: &Path) -> bool {
     let exts = ["nsp", "nsz", "nsc"];
@@ -564,6 +739,7 @@ async fn clear_game_path(
     if !game_path.exists() {
         return Err("Game path does not exist".to_string());
     }
+    let _lock = LocalFileLock::write(&game_path)?;
 
     // Get all active game IDs before removing them
     let active_game_ids: Vec<u32> = {
@@ -595,12 +771,9 @@ async fn clear_game_path(
                 }),
             );
 
-            // Pause torrent first
-            if let Err(e) = state.session.pause(&handle).await {
-                warn!(
-                    "[Shard_Torrent_Backend] Failed to pause torrent {}: {}",
-                    game_id, e
-                );
+            // Pause torrent first, unless it is queued and paused already
+            if let Err(e) = state.pause_if_live(&handle).await {
+                warn!("[Shard_Torrent_Backend] {} for game {}", e, game_id);
             }
 
             // Remove from session
@@ -624,6 +797,8 @@ async fn clear_game_path(
                 game_id
             );
         }
+
+        state.forget_game(game_id).await;
     }
 
     // Small delay to allow async tasks to clean up
@@ -643,6 +818,15 @@ async fn clear_game_path(
                         Ok(_) => {
                             info!("[Shard_Torrent_Backend] Deleted directory: {:?}", path);
                             deleted_count += 1;
+
+                            // Game folders are named by id
+                            if let Some(game_id) = path
+                                .file_name()
+                                .and_then(|name| name.to_str())
+                                .and_then(|name| name.parse::<u32>().ok())
+                            {
+                                state.forget_game(game_id).await;
+                            }
                         }
                         Err(e) => {
                             error!("[Shard_Torrent_Backend] Failed to delete {:?}: {}", path, e);
@@ -722,12 +906,16 @@ pub fn run() {
             });
             app.manage(state);
 
+            // Initialize the registry of known consoles
+            app.manage(Arc::new(device_registry::DeviceRegistry::load()));
+
             // Initialize FTP Monitor state
             app.manage(Arc::new(Mutex::new(None::<ftp_discovery::FTPMonitor>)));
 
             // Initialize FTP Manager state
             let ftp_manager = ftp_manager::FTPManager::new(app.handle().clone());
             app.manage(Arc::new(Mutex::new(Some(ftp_manager))));
+            ftp_manager::listen_for_completed_downloads(app.handle());
 
             // Start all installed plugins
             if let Err(e) = plugin_manager::PluginManager::start_plugins() {
@@ -764,16 +952,51 @@ pub fn run() {
             ftp_discovery::start_ftp_monitor,
             ftp_discovery::stop_ftp_monitor,
             ftp_discovery::is_ftp_monitor_running,
+            ftp_discovery::get_local_interfaces,
+            ftp_discovery::get_discovery_settings,
+            ftp_discovery::set_discovery_settings,
+            // Device registry commands
+            device_registry::get_registered_devices,
+            device_registry::rename_device,
+            device_registry::set_preferred_device,
+            device_registry::set_device_install_target,
+            device_registry::merge_devices,
+            device_registry::forget_device,
+            // Remote file browser commands
+            ftp_browser::list_remote_dir,
+            ftp_browser::remote_mkdir,
+            ftp_browser::remote_delete,
+            ftp_browser::remote_rename,
+            ftp_browser::download_remote_file,
             // FTP Manager commands
             ftp_manager::set_ftp_ip,
             ftp_manager::get_ftp_ip,
+            ftp_manager::set_ftp_profile,
+            ftp_manager::get_ftp_profile,
+            ftp_manager::get_transfer_settings,
+            ftp_manager::set_transfer_settings,
+            ftp_manager::set_ftp_upload_limit,
+            ftp_manager::set_task_upload_limit,
+            ftp_connection::get_saved_ftp_profiles,
+            ftp_connection::remove_ftp_profile,
             ftp_manager::scan_game_files,
             ftp_manager::queue_file,
+            ftp_manager::queue_file_to_devices,
+            ftp_manager::scan_game_bundle,
+            ftp_manager::queue_game_bundle,
             ftp_manager::get_transfer_queue,
+            ftp_manager::get_transfer_tasks,
             ftp_manager::clear_transfer_queue,
             ftp_manager::remove_from_transfer_queue,
+            ftp_manager::retry_failed_transfers,
+            ftp_manager::clear_failed_transfers,
             ftp_manager::get_current_transfer,
+            ftp_manager::get_current_transfers,
+            ftp_manager::pause_ftp_transfer,
+            ftp_manager::resume_ftp_transfer,
+            ftp_manager::cancel_ftp_transfer,
             ftp_manager::is_ftp_transferring,
+            ftp_manager::diagnose_ftp_connection,
             // Plugin commands
             plugin_manager::get_available_plugins,
             plugin_manager::get_installed_plugins,
@@ -787,11 +1010,22 @@ pub fn run() {
             create_game_dir,
             obtain_torrent_file,
             download_game,
+            inspect_torrent,
+            verify_game_download,
             pause_game,
             resume_game,
             uninstall_game,
             extract_and_clean,
             get_active_downloads,
+            get_download_queue,
+            set_download_priority,
+            reorder_download,
+            move_download_to_top,
+            start_next_download,
+            get_torrent_settings,
+            set_torrent_settings,
+            get_game_rate_limits,
+            set_game_rate_limits,
             is_game_downloaded,
             clear_game_path
         ])
@@ -1,25 +1,228 @@
 use anyhow::Context;
-use librqbit::{AddTorrent, AddTorrentOptions, AddTorrentResponse, ManagedTorrent, Session};
+use librqbit::limits::LimitsConfig;
+use librqbit::{
+    AddTorrent, AddTorrentOptions, AddTorrentResponse, ManagedTorrent, Session, TorrentStatsState,
+};
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 use crate::configs::defaults::{get_config_path, get_game_path};
+use crate::configs::storage::{load_config_file, save_config_file};
+use crate::dbi::local_lock::LocalFileLock;
 
-#[derive(Serialize, Deserialize)]
+const TORRENT_CACHE_DIR: &str = "torrents";
+const TORRENT_SETTINGS_FILE: &str = ".torrent_settings.json";
+const TORRENT_METADATA_FILE: &str = ".torrent_metadata.json";
+const QUEUE_SCHEDULER_INTERVAL_SECS: u64 = 2;
+
+/// Decides where a game enters the download queue. Games are started in queue order;
+/// the priority only places new and re-prioritised games ahead of lower ones.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+/// Bandwidth caps in bytes per second. None means unlimited.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimits {
+    pub download_limit: Option<u32>,
+    pub upload_limit: Option<u32>,
+}
+
+impl RateLimits {
+    pub fn to_config(self) -> LimitsConfig {
+        LimitsConfig {
+            download_bps: self.download_limit.and_then(NonZeroU32::new),
+            upload_bps: self.upload_limit.and_then(NonZeroU32::new),
+        }
+    }
+}
+
+/// Download settings, saved to `.torrent_settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TorrentSettings {
+    /// Torrents downloading at once. Further games wait in the queue, paused.
+    pub max_active_downloads: usize,
+    /// Caps for the whole session, shared by every download.
+    pub rate_limits: RateLimits,
+}
+
+impl Default for TorrentSettings {
+    fn default() -> Self {
+        Self {
+            max_active_downloads: 2,
+            rate_limits: RateLimits::default(),
+        }
+    }
+}
+
+/// A file inside a game's torrent. `index` is what `download_game` takes to select it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TorrentFileEntry {
+    pub index: usize,
+    pub path: String,
+    pub size: u64,
+}
+
+/// Result of re-checking one file against the torrent's piece hashes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHealth {
+    pub index: usize,
+    pub path: String,
+    pub size: u64,
+    /// Bytes whose pieces passed the hash check.
+    pub verified_bytes: u64,
+    pub healthy: bool,
+}
+
+/// Outcome of `verify_game_download`. Bad pieces are queued for download again.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub game_id: u32,
+    pub healthy: bool,
+    pub corrupt_files: usize,
+    /// Whether the missing or corrupt pieces are being downloaded again.
+    pub repairing: bool,
+    pub files: Vec<FileHealth>,
+}
+
+/// A game waiting for a free download slot.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedDownload {
+    pub game_id: u32,
+    pub position: usize,
+    pub priority: DownloadPriority,
+}
+
+/// A download as written to `.torrent_state.json`. The metadata is cached by info hash,
+/// so restoring does not depend on `game.torrent`, which `extract_and_clean` deletes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SavedTorrent {
     game_id: u32,
-    torrent_path: String,
+    #[serde(default)]
+    info_hash: Option<String>,
+    /// Magnet URI the download was started from, used if the cached metadata is gone.
+    #[serde(default)]
+    magnet: Option<String>,
+    /// Path of the .torrent file, only written by older versions.
+    #[serde(default, skip_serializing)]
+    torrent_path: Option<String>,
+    #[serde(default)]
+    priority: DownloadPriority,
+    /// Place in the download queue, None once the download has started.
+    #[serde(default)]
+    queue_position: Option<usize>,
+    /// Caps for this game on top of the global ones.
+    #[serde(default)]
+    rate_limits: RateLimits,
+    /// Indexes of the torrent files to download, None for all of them.
+    #[serde(default)]
+    only_files: Option<Vec<usize>>,
+}
+
+/// The torrent a game was downloaded from, kept in `.torrent_metadata.json` for as long
+/// as the game is installed so it can still be verified after the download is done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GameMetadata {
+    /// Key of the metadata cached in the `torrents` folder.
+    info_hash: String,
+    #[serde(default)]
+    magnet: Option<String>,
+    #[serde(default)]
+    only_files: Option<Vec<usize>>,
+}
+
+impl GameMetadata {
+    fn source(&self) -> Option<TorrentSource> {
+        let cached = cached_torrent_path(&self.info_hash);
+        if cached.exists() {
+            return Some(TorrentSource::File(cached));
+        }
+        self.magnet.clone().map(TorrentSource::Magnet)
+    }
+}
+
+/// Where a download's metadata comes from.
+pub enum TorrentSource {
+    File(PathBuf),
+    Magnet(String),
+}
+
+impl TorrentSource {
+    /// The source for a game folder: `game.torrent` if present, otherwise the magnet
+    /// link saved in `game.magnet`.
+    pub fn for_game_dir(game_dir: &Path) -> Option<Self> {
+        let torrent_path = game_dir.join("game.torrent");
+        if torrent_path.exists() {
+            return Some(TorrentSource::File(torrent_path));
+        }
+
+        std::fs::read_to_string(game_dir.join("game.magnet"))
+            .ok()
+            .map(|magnet| magnet.trim().to_string())
+            .filter(|magnet| is_magnet_link(magnet))
+            .map(TorrentSource::Magnet)
+    }
+
+    pub fn to_add_torrent(&self) -> Result<AddTorrent<'static>, String> {
+        match self {
+            TorrentSource::File(path) => AddTorrent::from_local_filename(&path.to_string_lossy())
+                .map_err(|e| format!("Failed to read torrent file: {}", e)),
+            TorrentSource::Magnet(magnet) => Ok(AddTorrent::from_url(magnet.clone())),
+        }
+    }
+
+    fn magnet(&self) -> Option<String> {
+        match self {
+            TorrentSource::File(_) => None,
+            TorrentSource::Magnet(magnet) => Some(magnet.clone()),
+        }
+    }
 }
 
+pub fn is_magnet_link(url: &str) -> bool {
+    url.trim_start().to_lowercase().starts_with("magnet:")
+}
+
+fn cached_torrent_path(info_hash: &str) -> PathBuf {
+    PathBuf::from(&get_config_path())
+        .join(TORRENT_CACHE_DIR)
+        .join(format!("{}.torrent", info_hash))
+}
+
+/// Every field is shared, so clones are cheap and see the same downloads.
+#[derive(Clone)]
 pub struct TorrentState {
     pub session: Arc<Session>,
     pub handles: Arc<RwLock<HashMap<u32, (usize, Arc<ManagedTorrent>)>>>,
+    saved: Arc<RwLock<HashMap<u32, SavedTorrent>>>,
+    /// Torrent of every downloaded game, finished or not.
+    metadata: Arc<RwLock<HashMap<u32, GameMetadata>>>,
+    /// Stops the progress and completion tasks of a game's download once its handle is
+    /// replaced or removed, so they cannot act on the new one.
+    tasks: Arc<RwLock<HashMap<u32, CancellationToken>>>,
+    /// Games waiting for a download slot, next one first. They sit paused in the session.
+    queue: Arc<RwLock<Vec<u32>>>,
+    settings: Arc<RwLock<TorrentSettings>>,
 }
 
 impl TorrentState {
@@ -33,108 +236,31 @@ impl TorrentState {
             .await
             .context("error creating shared session")?;
 
-        let handles = Arc::new(RwLock::new(HashMap::new()));
+        let settings: TorrentSettings = load_config_file(TORRENT_SETTINGS_FILE).unwrap_or_default();
+        Self::apply_session_limits(&session, settings.rate_limits);
 
-        // Restore saved torrents
+        let mut saved_records = HashMap::new();
+        let mut pending = Vec::new();
+
+        // Read saved torrents; they are added back to the session once the state is up
         if state_file.exists() {
             info!("[Shard_Torrent_Backend] Found saved torrent state, restoring...");
 
             if let Ok(data) = tokio::fs::read_to_string(&state_file).await {
-                if let Ok(saved_torrents) = serde_json::from_str::<Vec<SavedTorrent>>(&data) {
+                if let Ok(mut saved_torrents) = serde_json::from_str::<Vec<SavedTorrent>>(&data) {
+                    // Running downloads first, then the queue in its saved order
+                    saved_torrents.sort_by_key(|saved| saved.queue_position.map_or(0, |p| p + 1));
+
                     info!(
                         "[Shard_Torrent_Backend] Restoring {} torrent(s)",
                         saved_torrents.len()
                     );
 
-                    let mut handles_guard = handles.write().await;
+                    // Records stay saved even if restoring fails, so they are tried again
+                    // on the next start instead of being lost
                     for saved in saved_torrents {
-                        let torrent_path = PathBuf::from(&saved.torrent_path);
-                        if torrent_path.exists() {
-                            if let Ok(add_torrent) =
-                                AddTorrent::from_local_filename(&saved.torrent_path)
-                            {
-                                let game_dir =
-                                    PathBuf::from(&get_game_path()).join(saved.game_id.to_string());
-
-                                match session
-                                    .add_torrent(
-                                        add_torrent,
-                                        Some(AddTorrentOptions {
-                                            overwrite: true,
-                                            output_folder: Some(
-                                                game_dir.to_string_lossy().to_string(),
-                                            ),
-                                            ..Default::default()
-                                        }),
-                                    )
-                                    .await
-                                {
-                                    Ok(response) => {
-                                        match response {
-                                            AddTorrentResponse::Added(id, handle)
-                                            | AddTorrentResponse::AlreadyManaged(id, handle) => {
-                                                info!(
-                                                    "[Shard_Torrent_Backend] Restored torrent for game {} with id: {}",
-                                                    saved.game_id, id
-                                                );
-
-                                                // Get initial stats to send to UI
-                                                let stats = handle.stats();
-                                                let progress_percent = (stats.progress_bytes
-                                                    as f64
-                                                    / stats.total_bytes.max(1) as f64)
-                                                    * 100.0;
-
-                                                // Emit download-restored event to notify UI
-                                                let _ = app_handle.emit(
-                                                    "download-restored",
-                                                    serde_json::json!({
-                                                        "gameId": saved.game_id,
-                                                        "state": format!("{:?}", stats.state),
-                                                        "progress": progress_percent,
-                                                        "downloadedBytes": stats.progress_bytes,
-                                                        "totalBytes": stats.total_bytes,
-                                                    }),
-                                                );
-
-                                                // UNPAUSE the torrent to resume downloading
-                                                if let Err(e) = session.unpause(&handle).await {
-                                                    warn!(
-                                                        "[Shard_Torrent_Backend] Failed to unpause game {}: {}",
-                                                        saved.game_id, e
-                                                    );
-                                                } else {
-                                                    info!(
-                                                        "[Shard_Torrent_Backend] Resumed download for game {}",
-                                                        saved.game_id
-                                                    );
-
-                                                    // Start progress monitoring for restored torrent
-                                                    Self::spawn_progress_monitor(
-                                                        saved.game_id,
-                                                        handle.clone(),
-                                                        app_handle.clone(),
-                                                    );
-                                                }
-                                                handles_guard.insert(saved.game_id, (id, handle));
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!(
-                                            "[Shard_Torrent_Backend] Failed to restore torrent for game {}: {}",
-                                            saved.game_id, e
-                                        );
-                                    }
-                                }
-                            }
-                        } else {
-                            warn!(
-                                "[Shard_Torrent_Backend] Torrent file not found for game {}, skipping",
-                                saved.game_id
-                            );
-                        }
+                        saved_records.insert(saved.game_id, saved.clone());
+                        pending.push(saved);
                     }
                 } else {
                     warn!("[Shard_Torrent_Backend] Failed to parse saved torrent state");
@@ -146,7 +272,55 @@ impl TorrentState {
             info!("[Shard_Torrent_Backend] No saved torrent state found");
         }
 
-        let state = Self { session, handles };
+        let state = Self {
+            session,
+            handles: Arc::new(RwLock::new(HashMap::new())),
+            saved: Arc::new(RwLock::new(saved_records)),
+            metadata: Arc::new(RwLock::new(
+                load_config_file(TORRENT_METADATA_FILE).unwrap_or_default(),
+            )),
+            tasks: Arc::new(RwLock::new(HashMap::new())),
+            queue: Arc::new(RwLock::new(Vec::new())),
+            settings: Arc::new(RwLock::new(settings)),
+        };
+
+        // Downloads saved by older versions have no metadata record yet
+        {
+            let saved = state.saved.read().await;
+            let mut metadata = state.metadata.write().await;
+            let missing: Vec<&SavedTorrent> = saved
+                .values()
+                .filter(|record| !metadata.contains_key(&record.game_id))
+                .collect();
+            for record in &missing {
+                if let Some(info_hash) = record.info_hash.clone() {
+                    metadata.insert(
+                        record.game_id,
+                        GameMetadata {
+                            info_hash,
+                            magnet: record.magnet.clone(),
+                            only_files: record.only_files.clone(),
+                        },
+                    );
+                }
+            }
+            if !missing.is_empty() {
+                if let Err(e) = save_config_file(TORRENT_METADATA_FILE, &*metadata) {
+                    warn!(
+                        "[Shard_Torrent_Backend] Failed to save torrent metadata: {}",
+                        e
+                    );
+                }
+            }
+        }
+
+        state.restore_downloads(pending, app_handle.clone());
+
+        info!(
+            "[Shard_Torrent_Backend] Starting download queue scheduler (interval: {}s)",
+            QUEUE_SCHEDULER_INTERVAL_SECS
+        );
+        state.start_queue_scheduler(app_handle.clone());
 
         // Start auto-save background task
         info!("[Shard_Torrent_Backend] Starting auto-save background task (interval: 5s)");
@@ -155,14 +329,897 @@ impl TorrentState {
         Ok(state)
     }
 
-    fn spawn_progress_monitor(game_id: u32, handle: Arc<ManagedTorrent>, app_handle: AppHandle) {
+    /// Adds saved downloads back to the session in the background, as fetching a magnet
+    /// link's metadata can take a long time or never finish while offline. Downloads from
+    /// cached metadata are resumed in their saved order; magnet links each get their own
+    /// task so one that hangs does not hold up the rest.
+    fn restore_downloads(&self, pending: Vec<SavedTorrent>, app_handle: AppHandle) {
+        let state = self.clone();
+        tokio::spawn(async move {
+            for saved in pending {
+                let Some(source) = Self::restore_source(&saved) else {
+                    warn!(
+                        "[Shard_Torrent_Backend] No torrent metadata or magnet link for game {}, skipping",
+                        saved.game_id
+                    );
+                    // Nothing to restore it from, not now or on a later start
+                    state.saved.write().await.remove(&saved.game_id);
+                    continue;
+                };
+
+                if matches!(source, TorrentSource::Magnet(_)) {
+                    let state = state.clone();
+                    let app_handle = app_handle.clone();
+                    tokio::spawn(async move {
+                        state.restore_download(saved, source, &app_handle).await;
+                    });
+                } else {
+                    state.restore_download(saved, source, &app_handle).await;
+                }
+            }
+        });
+    }
+
+    async fn restore_download(
+        &self,
+        saved: SavedTorrent,
+        source: TorrentSource,
+        app_handle: &AppHandle,
+    ) {
+        let add_torrent = match source.to_add_torrent() {
+            Ok(add_torrent) => add_torrent,
+            Err(e) => {
+                error!(
+                    "[Shard_Torrent_Backend] Failed to restore torrent for game {}: {}",
+                    saved.game_id, e
+                );
+                return;
+            }
+        };
+        let game_dir = PathBuf::from(&get_game_path()).join(saved.game_id.to_string());
+
+        let response = self
+            .session
+            .add_torrent(
+                add_torrent,
+                Some(AddTorrentOptions {
+                    // Started below, once the queue has a slot for it
+                    paused: true,
+                    overwrite: true,
+                    output_folder: Some(game_dir.to_string_lossy().to_string()),
+                    ratelimits: saved.rate_limits.to_config(),
+                    only_files: saved.only_files.clone(),
+                    ..Default::default()
+                }),
+            )
+            .await;
+        let (id, handle, added) = match response {
+            Ok(AddTorrentResponse::Added(id, handle)) => (id, handle, true),
+            Ok(AddTorrentResponse::AlreadyManaged(id, handle)) => (id, handle, false),
+            Ok(AddTorrentResponse::ListOnly(_)) => return,
+            Err(e) => {
+                error!(
+                    "[Shard_Torrent_Backend] Failed to restore torrent for game {}: {}",
+                    saved.game_id, e
+                );
+                return;
+            }
+        };
+
+        // The game may have been uninstalled, or downloaded again, while this was running
+        {
+            let mut handles_guard = self.handles.write().await;
+            let mut saved_guard = self.saved.write().await;
+            if !saved_guard.contains_key(&saved.game_id)
+                || handles_guard.contains_key(&saved.game_id)
+            {
+                drop(saved_guard);
+                drop(handles_guard);
+                info!(
+                    "[Shard_Torrent_Backend] Game {} changed while restoring, dropping the restored torrent",
+                    saved.game_id
+                );
+                // A torrent that was already in the session belongs to the newer download
+                if added {
+                    if let Err(e) = self
+                        .session
+                        .delete(librqbit::api::TorrentIdOrHash::Id(id), false)
+                        .await
+                    {
+                        warn!(
+                            "[Shard_Torrent_Backend] Failed to remove restored torrent {}: {}",
+                            id, e
+                        );
+                    }
+                }
+                return;
+            }
+
+            let mut record = saved.clone();
+            record.info_hash = Some(Self::cache_metadata(&handle, &source));
+            record.magnet = record.magnet.or_else(|| source.magnet());
+            saved_guard.insert(saved.game_id, record);
+            handles_guard.insert(saved.game_id, (id, handle.clone()));
+        }
+
+        info!(
+            "[Shard_Torrent_Backend] Restored torrent for game {} with id: {}",
+            saved.game_id, id
+        );
+
+        // Get initial stats to send to UI
+        let stats = handle.stats();
+        let progress_percent =
+            (stats.progress_bytes as f64 / stats.total_bytes.max(1) as f64) * 100.0;
+
+        // Emit download-restored event to notify UI
+        let _ = app_handle.emit(
+            "download-restored",
+            serde_json::json!({
+                "gameId": saved.game_id,
+                "state": format!("{:?}", stats.state),
+                "progress": progress_percent,
+                "downloadedBytes": stats.progress_bytes,
+                "totalBytes": stats.total_bytes,
+            }),
+        );
+
+        // Resume in the old order, queueing it if no slot is free
+        match self
+            .enqueue_or_start(saved.game_id, &handle, app_handle)
+            .await
+        {
+            Ok(true) => info!(
+                "[Shard_Torrent_Backend] Resumed download for game {}",
+                saved.game_id
+            ),
+            Ok(false) => info!(
+                "[Shard_Torrent_Backend] Queued restored download for game {}",
+                saved.game_id
+            ),
+            Err(e) => warn!(
+                "[Shard_Torrent_Backend] Failed to unpause game {}: {}",
+                saved.game_id, e
+            ),
+        }
+
+        // Start progress monitoring for restored torrent
+        let token = self.download_token(saved.game_id).await;
+        Self::spawn_progress_monitor(saved.game_id, handle, app_handle.clone(), token);
+    }
+
+    /// Picks the source to restore a saved download from: the cached metadata first,
+    /// then a .torrent file saved by an older version, then the magnet link.
+    fn restore_source(saved: &SavedTorrent) -> Option<TorrentSource> {
+        if let Some(info_hash) = saved.info_hash.as_deref() {
+            let cached = cached_torrent_path(info_hash);
+            if cached.exists() {
+                return Some(TorrentSource::File(cached));
+            }
+        }
+
+        if let Some(path) = saved.torrent_path.as_ref().map(PathBuf::from) {
+            if path.exists() {
+                return Some(TorrentSource::File(path));
+            }
+        }
+
+        saved.magnet.clone().map(TorrentSource::Magnet)
+    }
+
+    /// Writes the torrent's metadata to the cache, keyed by info hash, and returns the hash.
+    fn cache_metadata(handle: &ManagedTorrent, source: &TorrentSource) -> String {
+        let info_hash = handle.info_hash().as_string();
+        let cached = cached_torrent_path(&info_hash);
+        if cached.exists() {
+            return info_hash;
+        }
+
+        let bytes = match source {
+            TorrentSource::File(path) => std::fs::read(path).map_err(|e| e.to_string()),
+            TorrentSource::Magnet(_) => handle
+                .with_metadata(|metadata| metadata.torrent_bytes.to_vec())
+                .map_err(|e| e.to_string()),
+        };
+
+        let written = bytes.and_then(|bytes| {
+            if let Some(dir) = cached.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&cached, bytes).map_err(|e| e.to_string())
+        });
+        match written {
+            Ok(_) => info!(
+                "[Shard_Torrent_Backend] Cached torrent metadata for {}",
+                info_hash
+            ),
+            Err(e) => warn!(
+                "[Shard_Torrent_Backend] Failed to cache torrent metadata for {}: {}",
+                info_hash, e
+            ),
+        }
+
+        info_hash
+    }
+
+    /// Remembers how a download was started so it can be restored after a restart.
+    pub async fn remember_download(
+        &self,
+        game_id: u32,
+        handle: &ManagedTorrent,
+        source: &TorrentSource,
+        priority: DownloadPriority,
+        only_files: Option<Vec<usize>>,
+    ) {
+        let info_hash = Self::cache_metadata(handle, source);
+        {
+            let mut metadata = self.metadata.write().await;
+            metadata.insert(
+                game_id,
+                GameMetadata {
+                    info_hash: info_hash.clone(),
+                    magnet: source.magnet(),
+                    only_files: only_files.clone(),
+                },
+            );
+            if let Err(e) = save_config_file(TORRENT_METADATA_FILE, &*metadata) {
+                warn!(
+                    "[Shard_Torrent_Backend] Failed to save torrent metadata of game {}: {}",
+                    game_id, e
+                );
+            }
+        }
+
+        let mut saved = self.saved.write().await;
+        // Limits set for an earlier download of the game carry over
+        let rate_limits = saved
+            .get(&game_id)
+            .map(|record| record.rate_limits)
+            .unwrap_or_default();
+        saved.insert(
+            game_id,
+            SavedTorrent {
+                game_id,
+                info_hash: Some(info_hash),
+                magnet: source.magnet(),
+                torrent_path: None,
+                priority,
+                queue_position: None,
+                rate_limits,
+                only_files,
+            },
+        );
+    }
+
+    /// Drops what was kept to restore a game's download, once it has finished or the
+    /// game is uninstalled, so the next auto-save stops restoring it.
+    pub async fn forget_download(&self, game_id: u32) {
+        self.cancel_download_tasks(game_id).await;
+        self.saved.write().await.remove(&game_id);
+        self.queue.write().await.retain(|queued| *queued != game_id);
+    }
+
+    /// Token the background tasks of a game's new download stop on. Any tasks still
+    /// running for an earlier download of the game are stopped.
+    pub async fn download_token(&self, game_id: u32) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.tasks.write().await.insert(game_id, token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    async fn cancel_download_tasks(&self, game_id: u32) {
+        if let Some(token) = self.tasks.write().await.remove(&game_id) {
+            token.cancel();
+        }
+    }
+
+    /// Removes a game's handle, unless it has been replaced by a newer download already.
+    /// Returns whether the handle was still the current one.
+    pub async fn release_handle(&self, game_id: u32, handle: &Arc<ManagedTorrent>) -> bool {
+        let mut handles = self.handles.write().await;
+        let current = handles
+            .get(&game_id)
+            .is_some_and(|(_, current)| Arc::ptr_eq(current, handle));
+        if current {
+            handles.remove(&game_id);
+        }
+        current
+    }
+
+    /// Pauses a torrent that is running. Queued downloads already sit paused and the
+    /// session refuses to pause them again, so anything that is not live is left alone.
+    pub async fn pause_if_live(&self, handle: &Arc<ManagedTorrent>) -> Result<(), String> {
+        if !matches!(handle.stats().state, TorrentStatsState::Live) {
+            return Ok(());
+        }
+        self.session
+            .pause(handle)
+            .await
+            .map_err(|e| format!("Failed to pause torrent: {}", e))
+    }
+
+    /// Forgets a game entirely once its files are deleted, including the torrent kept
+    /// for verifying it.
+    pub async fn forget_game(&self, game_id: u32) {
+        self.forget_download(game_id).await;
+
+        let mut metadata = self.metadata.write().await;
+        if metadata.remove(&game_id).is_some() {
+            if let Err(e) = save_config_file(TORRENT_METADATA_FILE, &*metadata) {
+                warn!(
+                    "[Shard_Torrent_Backend] Failed to save torrent metadata: {}",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Re-checks every piece of a game's files against its torrent. The torrent is
+    /// removed from the session, keeping the files, and added again, which hashes what is
+    /// on disk; only the pieces that fail are then downloaded again.
+    pub async fn verify(
+        &self,
+        game_id: u32,
+        app_handle: &AppHandle,
+    ) -> Result<VerifyReport, String> {
+        let game_dir = PathBuf::from(&get_game_path()).join(game_id.to_string());
+        if !game_dir.exists() {
+            return Err(format!(
+                "Game directory does not exist for game id {}",
+                game_id
+            ));
+        }
+        // Files being sent to a console must not be rewritten under the upload
+        let _lock = LocalFileLock::write(&game_dir)?;
+
+        // Finished games are no longer in the restorable list, only in the metadata record
+        let saved = self.saved.read().await.get(&game_id).cloned();
+        let metadata = self.metadata.read().await.get(&game_id).cloned();
+        let source = saved
+            .as_ref()
+            .and_then(Self::restore_source)
+            .or_else(|| metadata.as_ref().and_then(GameMetadata::source))
+            .or_else(|| TorrentSource::for_game_dir(&game_dir))
+            .ok_or_else(|| format!("No torrent metadata found for game id {}", game_id))?;
+        let only_files = match &saved {
+            Some(record) => record.only_files.clone(),
+            None => metadata
+                .as_ref()
+                .and_then(|record| record.only_files.clone()),
+        };
+
+        // Remove the torrent from the session so it forgets what it had verified. A game
+        // that finished in this session has no handle any more but its torrent is still
+        // there, and adding it again would return that one without hashing anything.
+        let existing = self.handles.read().await.get(&game_id).cloned();
+        let info_hash = saved
+            .as_ref()
+            .and_then(|record| record.info_hash.clone())
+            .or_else(|| metadata.as_ref().map(|record| record.info_hash.clone()));
+        let finished = info_hash.as_deref().and_then(|info_hash| {
+            self.session.with_torrents(|torrents| {
+                torrents
+                    .find(|(_, torrent)| torrent.info_hash().as_string() == info_hash)
+                    .map(|(id, _)| id)
+            })
+        });
+        let mut stale: Vec<usize> = existing.iter().map(|(id, _)| *id).collect();
+        if let Some(torrent_id) = finished.filter(|id| !stale.contains(id)) {
+            stale.push(torrent_id);
+        }
+        for torrent_id in stale {
+            // Deleting works whatever the state, so queued and paused torrents are fine
+            self.session
+                .delete(librqbit::api::TorrentIdOrHash::Id(torrent_id), false)
+                .await
+                .map_err(|e| format!("Failed to remove torrent: {}", e))?;
+        }
+        if existing.is_some() {
+            self.cancel_download_tasks(game_id).await;
+            self.handles.write().await.remove(&game_id);
+            self.dequeue(game_id).await;
+        }
+
+        info!(
+            "[Shard_Torrent_Backend] Verifying files of game {}",
+            game_id
+        );
+        let (torrent_id, handle) = match self
+            .session
+            .add_torrent(
+                source.to_add_torrent()?,
+                Some(AddTorrentOptions {
+                    paused: true,
+                    overwrite: true,
+                    output_folder: Some(game_dir.to_string_lossy().to_string()),
+                    ratelimits: self.game_rate_limits(game_id).await.to_config(),
+                    only_files: only_files.clone(),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .map_err(|e| format!("Failed to add torrent: {}", e))?
+        {
+            AddTorrentResponse::Added(id, handle) => (id, handle),
+            AddTorrentResponse::AlreadyManaged(id, _) => {
+                return Err(format!(
+                    "Torrent of game {} is still in the session as {}, its files were not checked",
+                    game_id, id
+                ));
+            }
+            AddTorrentResponse::ListOnly(_) => {
+                return Err("Torrent added in list-only mode".to_string());
+            }
+        };
+
+        // The initial check hashes every piece before the torrent settles as paused
+        let stats = loop {
+            let stats = handle.stats();
+            match stats.state {
+                TorrentStatsState::Initializing => {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                TorrentStatsState::Error => {
+                    return Err(format!(
+                        "Failed to check files: {}",
+                        stats.error.unwrap_or_default()
+                    ));
+                }
+                _ => break stats,
+            }
+        };
+
+        let files = handle
+            .with_metadata(|metadata| {
+                metadata
+                    .file_infos
+                    .iter()
+                    .zip(stats.file_progress.iter())
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        only_files
+                            .as_ref()
+                            .is_none_or(|selected| selected.contains(index))
+                    })
+                    .map(|(index, (file, verified))| FileHealth {
+                        index,
+                        path: file.relative_filename.to_string_lossy().to_string(),
+                        size: file.len,
+                        verified_bytes: *verified,
+                        healthy: *verified >= file.len,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let corrupt_files = files.iter().filter(|file| !file.healthy).count();
+
+        // Only a repair keeps the torrent around, as a download that is restored on restart
+        let repairing = !stats.finished;
+        if repairing {
+            warn!(
+                "[Shard_Torrent_Backend] Game {} has {} damaged file(s), downloading bad pieces again",
+                game_id, corrupt_files
+            );
+            self.handles
+                .write()
+                .await
+                .insert(game_id, (torrent_id, handle.clone()));
+            self.remember_download(
+                game_id,
+                &handle,
+                &source,
+                saved.map(|record| record.priority).unwrap_or_default(),
+                only_files,
+            )
+            .await;
+            self.enqueue_or_start(game_id, &handle, app_handle).await?;
+            let token = self.download_token(game_id).await;
+            Self::spawn_progress_monitor(game_id, handle, app_handle.clone(), token);
+        } else {
+            info!(
+                "[Shard_Torrent_Backend] All files of game {} passed verification",
+                game_id
+            );
+            self.forget_download(game_id).await;
+            self.session
+                .delete(librqbit::api::TorrentIdOrHash::Id(torrent_id), false)
+                .await
+                .map_err(|e| format!("Failed to remove torrent: {}", e))?;
+        }
+
+        Ok(VerifyReport {
+            game_id,
+            healthy: corrupt_files == 0,
+            corrupt_files,
+            repairing,
+            files,
+        })
+    }
+
+    /// Lists the files of a torrent without adding it. Magnet links wait for the
+    /// metadata to arrive from peers.
+    pub async fn list_files(
+        &self,
+        source: &TorrentSource,
+    ) -> Result<Vec<TorrentFileEntry>, String> {
+        let response = self
+            .session
+            .add_torrent(
+                source.to_add_torrent()?,
+                Some(AddTorrentOptions {
+                    list_only: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .map_err(|e| format!("Failed to read torrent: {}", e))?;
+
+        let AddTorrentResponse::ListOnly(list) = response else {
+            return Err("Torrent is already being downloaded".to_string());
+        };
+
+        list.info
+            .iter_filenames_and_lengths()
+            .map_err(|e| format!("Failed to list torrent files: {}", e))?
+            .enumerate()
+            .map(|(index, (name, size))| {
+                let path = name
+                    .to_string()
+                    .map_err(|e| format!("Invalid file name in torrent: {}", e))?;
+                Ok(TorrentFileEntry { index, path, size })
+            })
+            .collect()
+    }
+
+    /// Per-game caps to add a download of `game_id` with.
+    pub async fn game_rate_limits(&self, game_id: u32) -> RateLimits {
+        self.saved
+            .read()
+            .await
+            .get(&game_id)
+            .map(|record| record.rate_limits)
+            .unwrap_or_default()
+    }
+
+    /// Caps a single game. The limits apply right away if it is downloading and are
+    /// kept for restores and later downloads of the game.
+    pub async fn set_game_rate_limits(
+        &self,
+        game_id: u32,
+        rate_limits: RateLimits,
+    ) -> Result<(), String> {
+        {
+            let mut saved = self.saved.write().await;
+            let record = saved
+                .get_mut(&game_id)
+                .ok_or_else(|| format!("No download found for game id {}", game_id))?;
+            record.rate_limits = rate_limits;
+        }
+
+        if let Some((_, handle)) = self.handles.read().await.get(&game_id) {
+            let config = rate_limits.to_config();
+            handle.ratelimits.set_download_bps(config.download_bps);
+            handle.ratelimits.set_upload_bps(config.upload_bps);
+        }
+
+        info!(
+            "[Shard_Torrent_Backend] Rate limits of game {} set to {:?}",
+            game_id, rate_limits
+        );
+        Ok(())
+    }
+
+    fn apply_session_limits(session: &Session, rate_limits: RateLimits) {
+        let config = rate_limits.to_config();
+        session.ratelimits.set_download_bps(config.download_bps);
+        session.ratelimits.set_upload_bps(config.upload_bps);
+    }
+
+    /// Downloads that take up a slot: started, not paused and not finished.
+    fn count_active(handles: &HashMap<u32, (usize, Arc<ManagedTorrent>)>, queue: &[u32]) -> usize {
+        handles
+            .iter()
+            .filter(|(game_id, (_, handle))| {
+                let stats = handle.stats();
+                !queue.contains(game_id)
+                    && !stats.finished
+                    && !matches!(stats.state, TorrentStatsState::Paused)
+            })
+            .count()
+    }
+
+    /// Puts a game behind every queued game of the same or higher priority.
+    fn insert_by_priority(queue: &mut Vec<u32>, saved: &HashMap<u32, SavedTorrent>, game_id: u32) {
+        let priority_of = |id: &u32| saved.get(id).map(|s| s.priority).unwrap_or_default();
+        let priority = priority_of(&game_id);
+        let index = queue
+            .iter()
+            .position(|queued| priority_of(queued) > priority)
+            .unwrap_or(queue.len());
+        queue.insert(index, game_id);
+    }
+
+    /// Starts a freshly added, paused download if a slot is free, otherwise queues it.
+    /// Returns whether it was started.
+    pub async fn enqueue_or_start(
+        &self,
+        game_id: u32,
+        handle: &Arc<ManagedTorrent>,
+        app_handle: &AppHandle,
+    ) -> Result<bool, String> {
+        let max_active = self.settings.read().await.max_active_downloads.max(1);
+        let active = {
+            let handles = self.handles.read().await;
+            let queue = self.queue.read().await;
+            Self::count_active(&handles, &queue)
+        };
+
+        if active < max_active {
+            self.session
+                .unpause(handle)
+                .await
+                .map_err(|e| format!("Failed to start torrent: {}", e))?;
+            return Ok(true);
+        }
+
+        let position = {
+            let saved = self.saved.read().await;
+            let mut queue = self.queue.write().await;
+            Self::insert_by_priority(&mut queue, &saved, game_id);
+            queue.iter().position(|queued| *queued == game_id)
+        };
+        info!(
+            "[Shard_Torrent_Backend] {} download(s) active, queued game {} at position {:?}",
+            active, game_id, position
+        );
+        let _ = app_handle.emit(
+            "download-queued",
+            serde_json::json!({
+                "gameId": game_id,
+                "position": position,
+            }),
+        );
+        Ok(false)
+    }
+
+    pub fn queue(&self) -> Arc<RwLock<Vec<u32>>> {
+        Arc::clone(&self.queue)
+    }
+
+    /// Waits until a queued game has been given a slot, or removed from the queue.
+    pub async fn wait_while_queued(queue: &RwLock<Vec<u32>>, game_id: u32) {
+        while queue.read().await.contains(&game_id) {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    pub async fn get_queue(&self) -> Vec<QueuedDownload> {
+        let saved = self.saved.read().await;
+        self.queue
+            .read()
+            .await
+            .iter()
+            .enumerate()
+            .map(|(position, game_id)| QueuedDownload {
+                game_id: *game_id,
+                position,
+                priority: saved.get(game_id).map(|s| s.priority).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Takes a game out of the queue without starting it. Returns whether it was queued.
+    pub async fn dequeue(&self, game_id: u32) -> bool {
+        let mut queue = self.queue.write().await;
+        let original_len = queue.len();
+        queue.retain(|queued| *queued != game_id);
+        queue.len() < original_len
+    }
+
+    pub async fn set_priority(
+        &self,
+        game_id: u32,
+        priority: DownloadPriority,
+    ) -> Result<(), String> {
+        let mut saved = self.saved.write().await;
+        let record = saved
+            .get_mut(&game_id)
+            .ok_or_else(|| format!("No download found for game id {}", game_id))?;
+        record.priority = priority;
+
+        // A queued game moves to its new priority's place
+        let mut queue = self.queue.write().await;
+        if let Some(index) = queue.iter().position(|queued| *queued == game_id) {
+            queue.remove(index);
+            Self::insert_by_priority(&mut queue, &saved, game_id);
+        }
+
+        info!(
+            "[Shard_Torrent_Backend] Priority of game {} set to {:?}",
+            game_id, priority
+        );
+        Ok(())
+    }
+
+    /// Moves a queued game to `position`, 0 being the next to start.
+    pub async fn move_in_queue(&self, game_id: u32, position: usize) -> Result<(), String> {
+        let mut queue = self.queue.write().await;
+        let index = queue
+            .iter()
+            .position(|queued| *queued == game_id)
+            .ok_or_else(|| format!("Game {} is not queued", game_id))?;
+
+        queue.remove(index);
+        let position = position.min(queue.len());
+        queue.insert(position, game_id);
+        info!(
+            "[Shard_Torrent_Backend] Moved game {} to queue position {}",
+            game_id, position
+        );
+        Ok(())
+    }
+
+    /// Starts the next queued game now, even if every slot is taken.
+    pub async fn start_next(&self, app_handle: &AppHandle) -> Result<Option<u32>, String> {
+        Self::start_queued(&self.session, &self.handles, &self.queue, app_handle).await
+    }
+
+    /// Pops queued games until one starts. Games removed from the session are skipped.
+    async fn start_queued(
+        session: &Session,
+        handles: &RwLock<HashMap<u32, (usize, Arc<ManagedTorrent>)>>,
+        queue: &RwLock<Vec<u32>>,
+        app_handle: &AppHandle,
+    ) -> Result<Option<u32>, String> {
+        loop {
+            let next = {
+                let mut queue = queue.write().await;
+                if queue.is_empty() {
+                    return Ok(None);
+                }
+                queue.remove(0)
+            };
+
+            let Some((_, handle)) = handles.read().await.get(&next).cloned() else {
+                continue;
+            };
+
+            session
+                .unpause(&handle)
+                .await
+                .map_err(|e| format!("Failed to start torrent: {}", e))?;
+            info!(
+                "[Shard_Torrent_Backend] Started queued download for game {}",
+                next
+            );
+            let _ = app_handle.emit(
+                "download-started",
+                serde_json::json!({
+                    "gameId": next,
+                }),
+            );
+            return Ok(Some(next));
+        }
+    }
+
+    pub async fn get_settings(&self) -> TorrentSettings {
+        self.settings.read().await.clone()
+    }
+
+    pub async fn set_settings(&self, settings: TorrentSettings) -> Result<(), String> {
+        save_config_file(TORRENT_SETTINGS_FILE, &settings)?;
+        Self::apply_session_limits(&self.session, settings.rate_limits);
+        info!(
+            "[Shard_Torrent_Backend] Updated torrent settings: {:?}",
+            settings
+        );
+        *self.settings.write().await = settings;
+        Ok(())
+    }
+
+    /// Starts queued games whenever a slot frees up: a download finished, was paused
+    /// or removed, or the limit was raised.
+    fn start_queue_scheduler(&self, app_handle: AppHandle) {
+        let session = Arc::clone(&self.session);
+        let handles = Arc::clone(&self.handles);
+        let queue = Arc::clone(&self.queue);
+        let settings = Arc::clone(&self.settings);
+
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(QUEUE_SCHEDULER_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+
+                let max_active = settings.read().await.max_active_downloads.max(1);
+                let active = {
+                    let handles = handles.read().await;
+                    let mut queue = queue.write().await;
+                    // Forget games that were uninstalled while waiting
+                    queue.retain(|game_id| handles.contains_key(game_id));
+                    Self::count_active(&handles, &queue)
+                };
+
+                for _ in active..max_active {
+                    match Self::start_queued(&session, &handles, &queue, &app_handle).await {
+                        Ok(Some(_)) => {}
+                        Ok(None) => break,
+                        Err(e) => {
+                            error!(
+                                "[Shard_Torrent_Backend] Failed to start queued download: {}",
+                                e
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Games whose torrent has not finished, with the files (relative to the game folder)
+    /// that are still being written. An empty list means the whole game is in flux, e.g.
+    /// while metadata is still being fetched. Blocks briefly if the handles are being
+    /// updated, so only call it from synchronous code; returns None if they stay busy.
+    pub fn incomplete_files(&self) -> Option<HashMap<u32, Vec<PathBuf>>> {
+        let handles = (0..20).find_map(|_| match self.handles.try_read() {
+            Ok(guard) => Some(guard),
+            Err(_) => {
+                std::thread::sleep(Duration::from_millis(10));
+                None
+            }
+        })?;
+
+        let mut incomplete = HashMap::new();
+        for (game_id, (_, handle)) in handles.iter() {
+            let stats = handle.stats();
+            if stats.finished {
+                continue;
+            }
+
+            let files = handle
+                .with_metadata(|metadata| {
+                    metadata
+                        .file_infos
+                        .iter()
+                        .zip(stats.file_progress.iter())
+                        .filter(|(file, downloaded)| **downloaded < file.len)
+                        .map(|(file, _)| file.relative_filename.clone())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            incomplete.insert(*game_id, files);
+        }
+
+        Some(incomplete)
+    }
+
+    fn spawn_progress_monitor(
+        game_id: u32,
+        handle: Arc<ManagedTorrent>,
+        app_handle: AppHandle,
+        token: CancellationToken,
+    ) {
         tokio::spawn({
             let start_time = std::time::Instant::now();
             async move {
+                // Whether pieces were still missing once the initial check was done
+                let mut downloading = false;
                 loop {
                     tokio::time::sleep(Duration::from_secs(1)).await;
+                    if token.is_cancelled() {
+                        info!(
+                            "[Shard_Torrent_Backend] Download of game {} was replaced or removed, stopping progress monitor",
+                            game_id
+                        );
+                        break;
+                    }
                     let stats = handle.stats();
                     let elapsed = start_time.elapsed().as_secs();
+                    downloading |=
+                        matches!(stats.state, TorrentStatsState::Live) && !stats.finished;
 
                     if let Some(live) = &stats.live {
                         let progress_percent =
@@ -237,8 +1294,16 @@ impl TorrentState {
                             "download-complete",
                             serde_json::json!({
                                 "gameId": game_id,
+                                "live": downloading,
                             }),
                         );
+
+                        // Finished games are not restored or downloaded again
+                        if let Some(state) = app_handle.try_state::<TorrentState>() {
+                            if state.release_handle(game_id, &handle).await {
+                                state.forget_download(game_id).await;
+                            }
+                        }
                         break;
                     }
                 }
@@ -248,6 +1313,8 @@ impl TorrentState {
 
     fn start_auto_save(&self, interval_secs: u16) {
         let handles = Arc::clone(&self.handles);
+        let saved = Arc::clone(&self.saved);
+        let queue = Arc::clone(&self.queue);
         let state_file = PathBuf::from(&get_config_path()).join(".torrent_state.json");
 
         tokio::spawn(async move {
@@ -256,47 +1323,132 @@ impl TorrentState {
                 interval.tick().await;
 
                 let handles_guard = handles.read().await;
+                let saved_guard = saved.read().await;
+                let queue_guard = queue.read().await;
                 let mut saved_torrents = Vec::new();
 
-                for (game_id, (_, _handle)) in handles_guard.iter() {
-                    // Save the torrent file path
-                    let torrent_path = PathBuf::from(&get_game_path())
-                        .join(game_id.to_string())
-                        .join("game.torrent");
-
-                    if torrent_path.exists() {
-                        saved_torrents.push(SavedTorrent {
-                            game_id: *game_id,
-                            torrent_path: torrent_path.to_string_lossy().to_string(),
-                        });
-                    }
+                for (game_id, (_, handle)) in handles_guard.iter() {
+                    // Save the info hash and magnet link the download was started with
+                    let mut record =
+                        saved_guard
+                            .get(game_id)
+                            .cloned()
+                            .unwrap_or_else(|| SavedTorrent {
+                                game_id: *game_id,
+                                info_hash: Some(handle.info_hash().as_string()),
+                                magnet: None,
+                                torrent_path: None,
+                                priority: DownloadPriority::default(),
+                                queue_position: None,
+                                rate_limits: RateLimits::default(),
+                                only_files: None,
+                            });
+                    record.queue_position = queue_guard.iter().position(|queued| queued == game_id);
+                    saved_torrents.push(record);
                 }
+                // Downloads that could not be restored yet keep their record as it was
+                saved_torrents.extend(
+                    saved_guard
+                        .values()
+                        .filter(|record| !handles_guard.contains_key(&record.game_id))
+                        .cloned(),
+                );
+                drop(queue_guard);
+                drop(saved_guard);
+                drop(handles_guard);
 
-                if !saved_torrents.is_empty() {
-                    match serde_json::to_string(&saved_torrents) {
-                        Ok(json) => match tokio::fs::write(&state_file, json).await {
-                            Ok(_) => {
-                                info!(
-                                    "[Shard_Torrent_Backend] Auto-saved state for {} torrent(s)",
-                                    saved_torrents.len()
-                                );
-                            }
-                            Err(e) => {
-                                error!(
-                                    "[Shard_Torrent_Backend] Failed to write torrent state: {}",
-                                    e
-                                );
-                            }
-                        },
+                // An empty list is written too, or finished and removed games come back
+                match serde_json::to_string(&saved_torrents) {
+                    Ok(json) => match tokio::fs::write(&state_file, json).await {
+                        Ok(_) => {
+                            info!(
+                                "[Shard_Torrent_Backend] Auto-saved state for {} torrent(s)",
+                                saved_torrents.len()
+                            );
+                        }
                         Err(e) => {
                             error!(
-                                "[Shard_Torrent_Backend] Failed to serialize torrent state: {}",
+                                "[Shard_Torrent_Backend] Failed to write torrent state: {}",
                                 e
                             );
                         }
+                    },
+                    Err(e) => {
+                        error!(
+                            "[Shard_Torrent_Backend] Failed to serialize torrent state: {}",
+                            e
+                        );
                     }
                 }
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_with(priorities: &[(u32, DownloadPriority)]) -> HashMap<u32, SavedTorrent> {
+        priorities
+            .iter()
+            .map(|(game_id, priority)| {
+                (
+                    *game_id,
+                    SavedTorrent {
+                        game_id: *game_id,
+                        info_hash: None,
+                        magnet: None,
+                        torrent_path: None,
+                        priority: *priority,
+                        queue_position: None,
+                        rate_limits: RateLimits::default(),
+                        only_files: None,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn insert_by_priority_keeps_arrival_order_within_a_priority() {
+        let saved = saved_with(&[
+            (1, DownloadPriority::Normal),
+            (2, DownloadPriority::Normal),
+            (3, DownloadPriority::Normal),
+        ]);
+        let mut queue = Vec::new();
+        for game_id in [1, 2, 3] {
+            TorrentState::insert_by_priority(&mut queue, &saved, game_id);
+        }
+        assert_eq!(queue, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn insert_by_priority_places_games_behind_equal_or_higher_ones() {
+        let saved = saved_with(&[
+            (1, DownloadPriority::High),
+            (2, DownloadPriority::Normal),
+            (3, DownloadPriority::Low),
+            (4, DownloadPriority::High),
+            (5, DownloadPriority::Normal),
+            (6, DownloadPriority::Low),
+        ]);
+        let mut queue = vec![1, 2, 3];
+
+        TorrentState::insert_by_priority(&mut queue, &saved, 4);
+        assert_eq!(queue, vec![1, 4, 2, 3]);
+        TorrentState::insert_by_priority(&mut queue, &saved, 5);
+        assert_eq!(queue, vec![1, 4, 2, 5, 3]);
+        TorrentState::insert_by_priority(&mut queue, &saved, 6);
+        assert_eq!(queue, vec![1, 4, 2, 5, 3, 6]);
+    }
+
+    #[test]
+    fn insert_by_priority_treats_unknown_games_as_normal() {
+        let saved = saved_with(&[(1, DownloadPriority::High), (2, DownloadPriority::Low)]);
+        let mut queue = vec![1, 2];
+        TorrentState::insert_by_priority(&mut queue, &saved, 7);
+        assert_eq!(queue, vec![1, 7, 2]);
+    }
+}